@@ -0,0 +1,829 @@
+// Conversions between bytes or text and encoded or escaped forms of
+// them. These are hand-written rather than generated, and don't use any
+// external crates.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::Utf8Error;
+
+// The alphabet to use for base64 conversions. `Standard` uses `+` and
+// `/` for the last two characters, `UrlSafe` uses `-` and `_`. Both
+// use `=` for padding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Base64Alphabet {
+    Standard,
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn chars(&self) -> &'static [u8; 64] {
+        match self {
+            Base64Alphabet::Standard => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+            }
+            Base64Alphabet::UrlSafe => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+            }
+        }
+    }
+
+    fn decode_char(&self, c: u8) -> Option<u8> {
+        self.chars().iter().position(|a| *a == c).map(|i| i as u8)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Base64Error {
+    // The input length is not a multiple of four.
+    InvalidLength(usize),
+    // A byte that is not in the alphabet was found at the given index.
+    InvalidCharacter { index: usize, byte: u8 },
+    // Padding was found somewhere other than the last one or two
+    // characters of the input.
+    InvalidPadding { index: usize },
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Base64Error::InvalidLength(len) => write!(
+                f,
+                "invalid base64 length {}, must be a multiple of four",
+                len
+            ),
+            Base64Error::InvalidCharacter { index, byte } => write!(
+                f,
+                "invalid base64 character {:?} at index {}",
+                char::from(*byte),
+                index
+            ),
+            Base64Error::InvalidPadding { index } => {
+                write!(f, "invalid base64 padding at index {}", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Base64Error {}
+
+// This never fails. The output uses the standard alphabet with `=`
+// padding.
+pub fn u8_slice_to_base64_string(input: &[u8]) -> String {
+    u8_slice_to_base64_string_with_alphabet(input, Base64Alphabet::Standard)
+}
+
+pub fn u8_slice_to_base64_string_with_alphabet(
+    input: &[u8],
+    alphabet: Base64Alphabet,
+) -> String {
+    let chars = alphabet.chars();
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n =
+            (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3f;
+                out.push(char::from(chars[index as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+// A Base64Error will be returned if the input is not a multiple of
+// four characters long, contains a character outside the standard
+// alphabet, or has padding anywhere other than the end.
+pub fn base64_str_to_u8_vec(input: &str) -> Result<Vec<u8>, Base64Error> {
+    base64_str_to_u8_vec_with_alphabet(input, Base64Alphabet::Standard)
+}
+
+pub fn base64_str_to_u8_vec_with_alphabet(
+    input: &str,
+    alphabet: Base64Alphabet,
+) -> Result<Vec<u8>, Base64Error> {
+    let bytes = input.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(Base64Error::InvalidLength(bytes.len()));
+    }
+
+    // Up to two trailing `=` are allowed.
+    let padding = if bytes.ends_with(b"==") {
+        2
+    } else if bytes.ends_with(b"=") {
+        1
+    } else {
+        0
+    };
+    let data_len = bytes.len() - padding;
+
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (chunk_index, chunk) in bytes.chunks(4).enumerate() {
+        let mut n = 0u32;
+        for (i, byte) in chunk.iter().enumerate() {
+            let index = chunk_index * 4 + i;
+            let value = if index >= data_len {
+                0
+            } else if *byte == b'=' {
+                return Err(Base64Error::InvalidPadding { index });
+            } else {
+                alphabet.decode_char(*byte).ok_or(
+                    Base64Error::InvalidCharacter { index, byte: *byte },
+                )?
+            };
+            n = (n << 6) | u32::from(value);
+        }
+
+        out.push((n >> 16) as u8);
+        out.push((n >> 8) as u8);
+        out.push(n as u8);
+    }
+    out.truncate(out.len() - padding);
+
+    Ok(out)
+}
+
+const BASE32_CHARS: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Base32Error {
+    // The input length is not a multiple of eight.
+    InvalidLength(usize),
+    // A byte that is not in the alphabet was found at the given index.
+    InvalidCharacter { index: usize, byte: u8 },
+    // Padding was found somewhere other than the end of the input, or
+    // the amount of padding doesn't match any input length.
+    InvalidPadding { index: usize },
+}
+
+impl fmt::Display for Base32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Base32Error::InvalidLength(len) => write!(
+                f,
+                "invalid base32 length {}, must be a multiple of eight",
+                len
+            ),
+            Base32Error::InvalidCharacter { index, byte } => write!(
+                f,
+                "invalid base32 character {:?} at index {}",
+                char::from(*byte),
+                index
+            ),
+            Base32Error::InvalidPadding { index } => {
+                write!(f, "invalid base32 padding at index {}", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Base32Error {}
+
+// This never fails. The output uses the RFC 4648 alphabet (`A-Z` and
+// `2-7`) with `=` padding.
+pub fn u8_slice_to_base32_string(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len().div_ceil(5) * 8);
+
+    for chunk in input.chunks(5) {
+        let mut n = 0u64;
+        for i in 0..5 {
+            n = (n << 8) | u64::from(chunk.get(i).copied().unwrap_or(0));
+        }
+
+        // Number of characters needed to hold the chunk's bits.
+        let used = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < used {
+                let index = (n >> (35 - 5 * i)) & 0x1f;
+                out.push(char::from(BASE32_CHARS[index as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+// A Base32Error will be returned if the input is not a multiple of
+// eight characters long, contains a character outside the RFC 4648
+// alphabet, or has invalid padding.
+pub fn base32_str_to_u8_vec(input: &str) -> Result<Vec<u8>, Base32Error> {
+    let bytes = input.as_bytes();
+    if !bytes.len().is_multiple_of(8) {
+        return Err(Base32Error::InvalidLength(bytes.len()));
+    }
+
+    let padding = bytes.iter().rev().take_while(|b| **b == b'=').count();
+    let data_len = bytes.len() - padding;
+    // Number of bytes in the last chunk for each amount of padding.
+    let last_chunk_len = match padding {
+        0 => 5,
+        1 => 4,
+        3 => 3,
+        4 => 2,
+        6 => 1,
+        _ => return Err(Base32Error::InvalidPadding { index: data_len }),
+    };
+
+    let mut out = Vec::with_capacity(bytes.len() / 8 * 5);
+    for (chunk_index, chunk) in bytes.chunks(8).enumerate() {
+        let mut n = 0u64;
+        for (i, byte) in chunk.iter().enumerate() {
+            let index = chunk_index * 8 + i;
+            let value = if index >= data_len {
+                0
+            } else if *byte == b'=' {
+                return Err(Base32Error::InvalidPadding { index });
+            } else {
+                BASE32_CHARS.iter().position(|c| c == byte).ok_or(
+                    Base32Error::InvalidCharacter { index, byte: *byte },
+                )? as u64
+            };
+            n = (n << 5) | value;
+        }
+        out.extend_from_slice(&n.to_be_bytes()[3..]);
+    }
+    out.truncate(out.len() - (5 - last_chunk_len));
+
+    Ok(out)
+}
+
+// Escapes the input for use inside a JSON string literal. The
+// surrounding quotes are not added. If nothing needs escaping the input
+// is borrowed, otherwise a new string is allocated.
+pub fn str_escape_json_cow(input: &str) -> Cow<'_, str> {
+    let needs_escape = |c: char| c == '"' || c == '\\' || c.is_control();
+    if !input.contains(needs_escape) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut out = String::with_capacity(input.len() + 2);
+    for c in input.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c.is_control() => {
+                out.push_str(&format!("\\u{:04x}", u32::from(c)))
+            }
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+// Error returned by the length-prefixed conversions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LengthPrefixError {
+    // The input is longer than `u32::MAX` bytes, so its length doesn't
+    // fit in the prefix.
+    TooLong(usize),
+    // The input is shorter than the prefix says it should be.
+    Truncated { needed: usize, actual: usize },
+    // The framed bytes are not valid UTF-8.
+    Utf8(Utf8Error),
+}
+
+impl fmt::Display for LengthPrefixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LengthPrefixError::TooLong(len) => write!(
+                f,
+                "length {} does not fit in a 32-bit length prefix",
+                len
+            ),
+            LengthPrefixError::Truncated { needed, actual } => write!(
+                f,
+                "truncated input: needed {} bytes, got {}",
+                needed, actual
+            ),
+            LengthPrefixError::Utf8(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for LengthPrefixError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LengthPrefixError::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// The output is the length of the input in bytes as a 4-byte
+// big-endian integer, followed by the UTF-8 bytes. A LengthPrefixError
+// will be returned if the input is longer than `u32::MAX` bytes.
+pub fn str_to_length_prefixed_u8_vec(
+    input: &str,
+) -> Result<Vec<u8>, LengthPrefixError> {
+    let len = u32::try_from(input.len())
+        .map_err(|_| LengthPrefixError::TooLong(input.len()))?;
+
+    let mut out = Vec::with_capacity(4 + input.len());
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(input.as_bytes());
+    Ok(out)
+}
+
+// The inverse of `str_to_length_prefixed_u8_vec`. Returns the framed
+// string, borrowed from the input, and the total number of bytes
+// consumed including the prefix. Any bytes after the frame are ignored.
+//
+// A LengthPrefixError will be returned if the input is too short for
+// the prefix or the length it gives, or if the string is not valid
+// UTF-8.
+pub fn u8_slice_to_length_prefixed_str(
+    input: &[u8],
+) -> Result<(&str, usize), LengthPrefixError> {
+    let truncated = |needed| LengthPrefixError::Truncated {
+        needed,
+        actual: input.len(),
+    };
+
+    let prefix = input.get(..4).ok_or_else(|| truncated(4))?;
+    let len = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+    let end = (len as usize).saturating_add(4);
+
+    let bytes = input.get(4..end).ok_or_else(|| truncated(end))?;
+    let s = std::str::from_utf8(bytes).map_err(LengthPrefixError::Utf8)?;
+    Ok((s, end))
+}
+
+// Escapes the input for use inside a C string literal. The surrounding
+// quotes are not added. `\`, `"`, and the common control characters
+// use their short escapes. Every other byte outside printable ASCII,
+// including each byte of a non-ASCII character, is written as a
+// three-digit octal escape such as `\303`. Octal is used rather than
+// `\xNN` because a C hex escape continues through any following hex
+// digits, so "\xffa" would be read as a single escape. A `?` that
+// follows another `?` is escaped to avoid forming a trigraph.
+pub fn str_to_c_literal_bytes(input: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() + 2);
+    let mut prev = 0;
+    for byte in input.bytes() {
+        match byte {
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            b'?' if prev == b'?' => out.extend_from_slice(b"\\?"),
+            b' '..=b'~' => out.push(byte),
+            _ => out.extend_from_slice(format!("\\{:03o}", byte).as_bytes()),
+        }
+        prev = byte;
+    }
+    out
+}
+
+// Error returned by `deflate_u8_slice_to_string`.
+#[cfg(feature = "compression")]
+#[derive(Debug)]
+pub enum DeflateError {
+    // The input is not a valid deflate stream.
+    Corrupt(std::io::Error),
+    // The decompressed bytes are not valid UTF-8.
+    Utf8(std::string::FromUtf8Error),
+}
+
+#[cfg(feature = "compression")]
+impl fmt::Display for DeflateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeflateError::Corrupt(err) => {
+                write!(f, "invalid deflate data: {}", err)
+            }
+            DeflateError::Utf8(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+impl std::error::Error for DeflateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeflateError::Corrupt(err) => Some(err),
+            DeflateError::Utf8(err) => Some(err),
+        }
+    }
+}
+
+// This conversion requires the `compression` feature.
+//
+// This never fails. The output is a raw deflate stream (RFC 1951),
+// without a zlib or gzip header, compressed at the default level.
+#[cfg(feature = "compression")]
+pub fn str_to_deflate_u8_vec(input: &str) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::DeflateEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    );
+    // Writing to a `Vec` can't fail.
+    encoder
+        .write_all(input.as_bytes())
+        .expect("writing to a Vec failed");
+    encoder.finish().expect("writing to a Vec failed")
+}
+
+// This conversion requires the `compression` feature.
+//
+// The inverse of `str_to_deflate_u8_vec`. A DeflateError will be
+// returned if the input is not a valid raw deflate stream or the
+// decompressed bytes are not valid UTF-8.
+#[cfg(feature = "compression")]
+pub fn deflate_u8_slice_to_string(
+    input: &[u8],
+) -> Result<String, DeflateError> {
+    use std::io::Read;
+
+    let mut bytes = Vec::new();
+    flate2::read::DeflateDecoder::new(input)
+        .read_to_end(&mut bytes)
+        .map_err(DeflateError::Corrupt)?;
+    String::from_utf8(bytes).map_err(DeflateError::Utf8)
+}
+
+// This never fails. Formats the input like the output of `xxd`: each
+// row has the offset as eight hex digits, up to 16 bytes in hex in
+// groups of two, and the same bytes as ASCII with `.` for anything
+// that isn't printable. The hex of a short final row is padded with
+// spaces so its ASCII column lines up. Every row ends with a newline;
+// an empty input gives an empty string.
+pub fn u8_slice_to_hex_dump_string(input: &[u8]) -> String {
+    let mut out = String::new();
+    for (row, chunk) in input.chunks(16).enumerate() {
+        let mut hex = String::with_capacity(39);
+        for (i, byte) in chunk.iter().enumerate() {
+            if i > 0 && i % 2 == 0 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x}", byte));
+        }
+        let ascii = chunk
+            .iter()
+            .map(|b| {
+                if (b' '..=b'~').contains(b) {
+                    char::from(*b)
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        out.push_str(&format!("{:08x}: {:<39}  {}\n", row * 16, hex, ascii));
+    }
+    out
+}
+
+// This never fails. Shifts each ASCII letter `shift` places forward in
+// the alphabet, wrapping from `z` to `a` and keeping its case. All
+// other characters, including digits and non-ASCII letters, are
+// unchanged. Shifting by `26 - shift` reverses it.
+pub fn str_to_caesar_string(input: &str, shift: u8) -> String {
+    let shift = shift % 26;
+    input
+        .chars()
+        .map(|c| {
+            let base = match c {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return c,
+            };
+            char::from(base + (c as u8 - base + shift) % 26)
+        })
+        .collect()
+}
+
+// This never fails. The same as `str_to_caesar_string` with a shift of
+// 13, so applying it twice gives back the input.
+pub fn str_to_rot13_string(input: &str) -> String {
+    str_to_caesar_string(input, 13)
+}
+
+// Lookup table for the CRC-32 used by zlib, PNG, and Ethernet
+// (reflected polynomial 0xEDB88320), built at compile time.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, b| {
+        CRC32_TABLE[usize::from((crc as u8) ^ b)] ^ (crc >> 8)
+    })
+}
+
+// Error returned by `crc32_framed_u8_slice_to_string`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Crc32FrameError {
+    // The input is shorter than the 4-byte checksum.
+    TooShort(usize),
+    // The checksum doesn't match the data, so it has been corrupted.
+    Mismatch { expected: u32, actual: u32 },
+    // The checksum matches but the data is not valid UTF-8.
+    Utf8(Utf8Error),
+}
+
+impl fmt::Display for Crc32FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Crc32FrameError::TooShort(len) => write!(
+                f,
+                "input length {} is too short for a CRC-32 trailer",
+                len
+            ),
+            Crc32FrameError::Mismatch { expected, actual } => write!(
+                f,
+                "CRC-32 mismatch: expected {:08x}, got {:08x}",
+                expected, actual
+            ),
+            Crc32FrameError::Utf8(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Crc32FrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Crc32FrameError::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// This never fails. The output is the UTF-8 bytes of the input
+// followed by their CRC-32 (as used by zlib and PNG) as a 4-byte
+// big-endian integer.
+pub fn str_to_crc32_framed_u8_vec(input: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() + 4);
+    out.extend_from_slice(input.as_bytes());
+    out.extend_from_slice(&crc32(input.as_bytes()).to_be_bytes());
+    out
+}
+
+// The inverse of `str_to_crc32_framed_u8_vec`. The checksum is
+// verified before the data is checked for UTF-8, so corrupted data is
+// reported as a mismatch.
+//
+// A Crc32FrameError will be returned if the input is too short to
+// have a checksum, the checksum doesn't match, or the data is not
+// valid UTF-8.
+pub fn crc32_framed_u8_slice_to_string(
+    input: &[u8],
+) -> Result<String, Crc32FrameError> {
+    if input.len() < 4 {
+        return Err(Crc32FrameError::TooShort(input.len()));
+    }
+    let (data, trailer) = input.split_at(input.len() - 4);
+
+    let expected =
+        u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let actual = crc32(data);
+    if expected != actual {
+        return Err(Crc32FrameError::Mismatch { expected, actual });
+    }
+
+    std::str::from_utf8(data)
+        .map(str::to_string)
+        .map_err(Crc32FrameError::Utf8)
+}
+
+// This never fails. Replaces each tab with enough spaces to reach the
+// next tab stop, where stops are every `tab_width` columns, so "a\tb"
+// with a width of 4 gives "a   b". The column restarts at zero after
+// each `\n`. Columns are counted in chars, so wide characters such as
+// CJK ideographs count as one column. If `tab_width` is zero tabs are
+// removed.
+pub fn str_to_tab_expanded_string(input: &str, tab_width: usize) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut column = 0;
+    for c in input.chars() {
+        match c {
+            '\t' => {
+                let spaces = if tab_width == 0 {
+                    0
+                } else {
+                    tab_width - column % tab_width
+                };
+                out.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' => {
+                out.push(c);
+                column = 0;
+            }
+            c => {
+                out.push(c);
+                column += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from RFC 4648.
+    const RFC_4648_VECTORS: &[(&str, &str, &str)] = &[
+        ("", "", ""),
+        ("f", "Zg==", "MY======"),
+        ("fo", "Zm8=", "MZXQ===="),
+        ("foo", "Zm9v", "MZXW6==="),
+        ("foob", "Zm9vYg==", "MZXW6YQ="),
+        ("fooba", "Zm9vYmE=", "MZXW6YTB"),
+        ("foobar", "Zm9vYmFy", "MZXW6YTBOI======"),
+    ];
+
+    #[test]
+    fn test_base64_vectors() {
+        for (decoded, base64, _) in RFC_4648_VECTORS {
+            assert_eq!(u8_slice_to_base64_string(decoded.as_bytes()), *base64);
+            assert_eq!(
+                base64_str_to_u8_vec(base64).unwrap(),
+                decoded.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_base32_vectors() {
+        for (decoded, _, base32) in RFC_4648_VECTORS {
+            assert_eq!(u8_slice_to_base32_string(decoded.as_bytes()), *base32);
+            assert_eq!(
+                base32_str_to_u8_vec(base32).unwrap(),
+                decoded.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_base32_invalid() {
+        assert_eq!(
+            base32_str_to_u8_vec("MY====="),
+            Err(Base32Error::InvalidLength(7))
+        );
+        assert_eq!(
+            base32_str_to_u8_vec("my======"),
+            Err(Base32Error::InvalidCharacter {
+                index: 0,
+                byte: b'm'
+            })
+        );
+        assert_eq!(
+            base32_str_to_u8_vec("M1======"),
+            Err(Base32Error::InvalidCharacter {
+                index: 1,
+                byte: b'1'
+            })
+        );
+        // Seven and two padding characters don't match any input
+        // length.
+        assert_eq!(
+            base32_str_to_u8_vec("M======="),
+            Err(Base32Error::InvalidPadding { index: 1 })
+        );
+        assert_eq!(
+            base32_str_to_u8_vec("MZXW6Y=="),
+            Err(Base32Error::InvalidPadding { index: 6 })
+        );
+        // Padding in the middle of the input.
+        assert_eq!(
+            base32_str_to_u8_vec("MY=A===="),
+            Err(Base32Error::InvalidPadding { index: 2 })
+        );
+        assert_eq!(
+            base32_str_to_u8_vec("MY======MZXW6YTB"),
+            Err(Base32Error::InvalidPadding { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_base64_alphabets() {
+        let input = [0xfb, 0xff];
+        assert_eq!(u8_slice_to_base64_string(&input), "+/8=");
+        assert_eq!(
+            u8_slice_to_base64_string_with_alphabet(
+                &input,
+                Base64Alphabet::UrlSafe
+            ),
+            "-_8="
+        );
+        assert_eq!(
+            base64_str_to_u8_vec_with_alphabet("-_8=", Base64Alphabet::UrlSafe)
+                .unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_base64_invalid() {
+        assert_eq!(
+            base64_str_to_u8_vec("Zm9"),
+            Err(Base64Error::InvalidLength(3))
+        );
+        assert_eq!(
+            base64_str_to_u8_vec("Zm9*"),
+            Err(Base64Error::InvalidCharacter {
+                index: 3,
+                byte: b'*'
+            })
+        );
+        // A URL-safe character is not in the standard alphabet.
+        assert_eq!(
+            base64_str_to_u8_vec("-_8="),
+            Err(Base64Error::InvalidCharacter {
+                index: 0,
+                byte: b'-'
+            })
+        );
+        assert_eq!(
+            base64_str_to_u8_vec("Z=9v"),
+            Err(Base64Error::InvalidPadding { index: 1 })
+        );
+        assert_eq!(
+            base64_str_to_u8_vec("Zm==Zm9v"),
+            Err(Base64Error::InvalidPadding { index: 2 })
+        );
+        assert_eq!(
+            base64_str_to_u8_vec("Z==="),
+            Err(Base64Error::InvalidPadding { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        // The standard check value for CRC-32/ISO-HDLC.
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_crc32_framed_round_trip() {
+        let framed = str_to_crc32_framed_u8_vec("123456789");
+        assert_eq!(framed[9..], [0xcb, 0xf4, 0x39, 0x26]);
+        assert_eq!(
+            crc32_framed_u8_slice_to_string(&framed).unwrap(),
+            "123456789"
+        );
+    }
+
+    #[test]
+    fn test_crc32_framed_invalid() {
+        let mut framed = str_to_crc32_framed_u8_vec("123456789");
+        framed[4] ^= 0x01;
+        assert_eq!(
+            crc32_framed_u8_slice_to_string(&framed),
+            Err(Crc32FrameError::Mismatch {
+                expected: 0xcbf4_3926,
+                actual: crc32(b"123446789"),
+            })
+        );
+
+        assert_eq!(
+            crc32_framed_u8_slice_to_string(&[0; 3]),
+            Err(Crc32FrameError::TooShort(3))
+        );
+
+        let mut framed = vec![0xff];
+        framed.extend_from_slice(&crc32(&[0xff]).to_be_bytes());
+        assert!(matches!(
+            crc32_framed_u8_slice_to_string(&framed),
+            Err(Crc32FrameError::Utf8(_))
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+use std::ffi::CStr;
+use std::ffi::CString;
+
+// This never fails and reuses the allocation.
+pub fn box_c_str_to_c_string(input: Box<CStr>) -> CString {
+    input.into_c_string()
+}
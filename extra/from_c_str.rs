@@ -0,0 +1,142 @@
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::ffi::OsString;
+use std::fmt;
+use std::num::ParseIntError;
+use std::path::PathBuf;
+use std::str::Utf8Error;
+
+// Returns the length of the input in bytes, not counting the nul
+// terminator. This can be used to preallocate a buffer before copying
+// many strings.
+pub fn c_str_len_without_nul(input: &CStr) -> usize {
+    input.to_bytes().len()
+}
+
+// Each item is copied into a new allocation, as with
+// `c_str_to_c_string`.
+pub fn c_str_iter_to_c_string_vec<'a>(
+    items: impl IntoIterator<Item = &'a CStr>,
+) -> Vec<CString> {
+    items.into_iter().map(CString::from).collect()
+}
+
+// Validates the input as UTF-8 and skips a leading byte order mark
+// (`EF BB BF`) if present. The result borrows from the input.
+pub fn c_str_to_str_strip_bom(input: &CStr) -> Result<&str, Utf8Error> {
+    input
+        .to_str()
+        .map(|s| s.strip_prefix('\u{feff}').unwrap_or(s))
+}
+
+// Validates the input as UTF-8 and trims trailing ASCII spaces, as
+// found in space-padded fixed-width fields. Leading spaces are kept.
+// The result borrows from the input.
+pub fn c_str_to_trimmed_str(input: &CStr) -> Result<&str, Utf8Error> {
+    input.to_str().map(|s| s.trim_end_matches(' '))
+}
+
+// This conversion is only allowed on Windows.
+//
+// This never fails. Windows paths are not stored as bytes, so unlike
+// `c_str_to_path_unix`, which borrows the input, this always allocates
+// a new `PathBuf`. The bytes are treated as UTF-8 and invalid
+// sequences are replaced with "�", so a path in another encoding (such
+// as the ANSI code page) will not round-trip.
+#[cfg(windows)]
+pub fn c_str_to_path_buf_windows(input: &CStr) -> PathBuf {
+    PathBuf::from(input.to_string_lossy().into_owned())
+}
+
+// This conversion is only allowed on Windows.
+//
+// This never fails. Windows strings are not stored as bytes, so unlike
+// `c_str_to_os_str_unix`, which borrows the input, this always
+// allocates a new `OsString`. The bytes are treated as UTF-8 and
+// invalid sequences are replaced with "�".
+#[cfg(windows)]
+pub fn c_str_to_os_string_windows(input: &CStr) -> OsString {
+    OsString::from(input.to_string_lossy().into_owned())
+}
+
+// Error returned when parsing the text of a `CStr`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    // The input is not valid UTF-8.
+    Utf8(Utf8Error),
+    // The input is not a valid integer.
+    Int(ParseIntError),
+    // The input is not one of the accepted boolean strings.
+    InvalidBool,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Utf8(err) => write!(f, "{}", err),
+            ParseError::Int(err) => write!(f, "{}", err),
+            ParseError::InvalidBool => {
+                write!(f, "expected one of \"0\", \"1\", \"true\" or \"false\"")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Utf8(err) => Some(err),
+            ParseError::Int(err) => Some(err),
+            ParseError::InvalidBool => None,
+        }
+    }
+}
+
+// Parses the input as a decimal integer, with an optional leading `+`
+// or `-`. Whitespace is not allowed.
+//
+// A ParseError will be returned if the input is not valid UTF-8 or is
+// not a valid `i64`.
+pub fn c_str_to_i64(input: &CStr) -> Result<i64, ParseError> {
+    input
+        .to_str()
+        .map_err(ParseError::Utf8)?
+        .parse()
+        .map_err(ParseError::Int)
+}
+
+// Accepts "1" or "true" for true, and "0" or "false" for false. Case
+// matters and whitespace is not allowed.
+//
+// A ParseError will be returned if the input is not valid UTF-8 or is
+// not one of those four strings.
+pub fn c_str_to_bool(input: &CStr) -> Result<bool, ParseError> {
+    match input.to_str().map_err(ParseError::Utf8)? {
+        "1" | "true" => Ok(true),
+        "0" | "false" => Ok(false),
+        _ => Err(ParseError::InvalidBool),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_str_to_str_strip_bom() {
+        let input = CStr::from_bytes_with_nul(b"\xef\xbb\xbfabc\0").unwrap();
+        let output = c_str_to_str_strip_bom(input).unwrap();
+        assert_eq!(output, "abc");
+        // The result borrows from the input, just past the BOM.
+        assert_eq!(output.as_ptr(), input.as_ptr().wrapping_add(3).cast());
+
+        let input = CStr::from_bytes_with_nul(b"abc\0").unwrap();
+        assert_eq!(c_str_to_str_strip_bom(input).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_c_str_to_trimmed_str() {
+        let input = CStr::from_bytes_with_nul(b"  ab c  \0").unwrap();
+        assert_eq!(c_str_to_trimmed_str(input).unwrap(), "  ab c");
+    }
+}
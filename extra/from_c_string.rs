@@ -0,0 +1,24 @@
+use std::ffi::CStr;
+use std::ffi::CString;
+
+// This never fails and reuses the allocation. A `Box<CStr>` is smaller
+// than a `CString` since it has no capacity, which helps when storing
+// many strings.
+pub fn c_string_to_box_c_str(input: CString) -> Box<CStr> {
+    input.into_boxed_c_str()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn test_c_string_to_path_buf_unix_reuses_allocation() {
+        let input = CString::new("a/b.txt").unwrap();
+        let ptr = input.as_ptr().cast::<u8>();
+        let output = c_string_to_path_buf_unix(input);
+        assert_eq!(output.as_os_str().as_bytes(), b"a/b.txt");
+        assert_eq!(output.as_os_str().as_bytes().as_ptr(), ptr);
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(unix)]
+
+use std::ffi::CString;
+use std::ffi::NulError;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+
+// This conversion is only allowed on Unix.
+//
+// Each entry is followed by a nul byte, as in an `envp` block. Note
+// that `execve` itself takes an array of pointers rather than a single
+// block; this is the serialized form.
+//
+// A NulError will be returned if any entry contains a nul byte.
+pub fn os_string_vec_to_nul_block_unix(
+    input: Vec<OsString>,
+) -> Result<Vec<u8>, NulError> {
+    let mut block = Vec::new();
+    for entry in input.into_iter() {
+        let entry = entry.into_vec();
+        if entry.contains(&0) {
+            // `CString::new` is the only way to construct a NulError.
+            return Err(CString::new(entry).unwrap_err());
+        }
+        block.extend_from_slice(&entry);
+        block.push(0);
+    }
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_os_string_vec_to_nul_block_unix() {
+        let input = vec![OsString::from("A=1"), OsString::from("B=2")];
+        assert_eq!(
+            os_string_vec_to_nul_block_unix(input).unwrap(),
+            b"A=1\0B=2\0"
+        );
+
+        let input = vec![OsString::from("A=1"), OsString::from("B\0=2")];
+        let err = os_string_vec_to_nul_block_unix(input).unwrap_err();
+        assert_eq!(err.nul_position(), 1);
+    }
+}
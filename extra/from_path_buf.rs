@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_buf_to_string_reuses_allocation() {
+        let input = PathBuf::from(String::with_capacity(16) + "a/b.txt");
+        let ptr = input.to_str().unwrap().as_ptr();
+        let output = path_buf_to_string(input).unwrap();
+        assert_eq!(output, "a/b.txt");
+        assert_eq!(output.as_ptr(), ptr);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_buf_to_u8_vec_unix_reuses_allocation() {
+        let input = PathBuf::from(OsString::from_vec(b"a/\xff".to_vec()));
+        let ptr = input.as_os_str().as_bytes().as_ptr();
+        let output = path_buf_to_u8_vec_unix(input);
+        assert_eq!(output, b"a/\xff");
+        assert_eq!(output.as_ptr(), ptr);
+    }
+}
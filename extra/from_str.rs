@@ -0,0 +1,543 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::ffi::NulError;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::fmt;
+
+// How a line produced by `str_to_lines_with_endings` was terminated,
+// or the ending to write with `str_to_u8_vec_with_line_endings`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    // Only the last line can have no ending.
+    None,
+}
+
+// Unlike `str::lines`, this reports the terminator of each line. The
+// lines borrow from the input and do not include the terminator. If
+// the input ends with a newline there is no trailing empty line, and
+// an empty input produces no lines.
+pub fn str_to_lines_with_endings(input: &str) -> Vec<(&str, LineEnding)> {
+    let mut lines = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        match rest.find('\n') {
+            Some(i) => {
+                let line = &rest[..i];
+                lines.push(match line.strip_suffix('\r') {
+                    Some(line) => (line, LineEnding::CrLf),
+                    None => (line, LineEnding::Lf),
+                });
+                rest = &rest[i + 1..];
+            }
+            None => {
+                lines.push((rest, LineEnding::None));
+                rest = "";
+            }
+        }
+    }
+    lines
+}
+
+// Error returned by `str_to_ascii_u8_vec`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NonAsciiError {
+    // Byte offset of the first non-ASCII character.
+    pub index: usize,
+    pub c: char,
+}
+
+impl fmt::Display for NonAsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "non-ASCII character {:?} at byte index {}",
+            self.c, self.index
+        )
+    }
+}
+
+impl std::error::Error for NonAsciiError {}
+
+// A NonAsciiError will be returned if the input contains any non-ASCII
+// characters. Unlike `str_to_u8_vec`, which passes multi-byte UTF-8
+// through, this guarantees one byte per character.
+pub fn str_to_ascii_u8_vec(input: &str) -> Result<Vec<u8>, NonAsciiError> {
+    match input.char_indices().find(|(_, c)| !c.is_ascii()) {
+        Some((index, c)) => Err(NonAsciiError { index, c }),
+        None => Ok(input.as_bytes().to_vec()),
+    }
+}
+
+// The output starts with a UTF-8 byte order mark (`EF BB BF`), as
+// expected by some Windows programs.
+pub fn str_to_u8_vec_with_bom(input: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() + 3);
+    out.extend_from_slice(b"\xef\xbb\xbf");
+    out.extend_from_slice(input.as_bytes());
+    out
+}
+
+// Splits the input into extended grapheme clusters, which is closer to
+// what a user sees as a single character than `chars()` is. For
+// example "e\u{301}" is one grapheme but two chars.
+#[cfg(feature = "unicode")]
+pub fn str_to_grapheme_vec(input: &str) -> Vec<&str> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    input.graphemes(true).collect()
+}
+
+// Without the `unicode` feature this is only an approximation: the
+// input is split into chars, so combining characters and emoji
+// sequences are not kept together.
+#[cfg(not(feature = "unicode"))]
+pub fn str_to_grapheme_vec(input: &str) -> Vec<&str> {
+    input
+        .char_indices()
+        .map(|(i, c)| &input[i..i + c.len_utf8()])
+        .collect()
+}
+
+// Keywords that can't be used as plain identifiers, as of the 2024
+// edition.
+const RUST_KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break",
+    "const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+    "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try",
+    "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+    "yield",
+];
+
+// Error returned by `str_to_rust_ident`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IdentError {
+    Empty,
+    // The identifier is just `_`.
+    Underscore,
+    // A character that isn't allowed at its position was found at the
+    // given byte index.
+    InvalidChar { index: usize, c: char },
+    Keyword(String),
+}
+
+impl fmt::Display for IdentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdentError::Empty => write!(f, "identifier is empty"),
+            IdentError::Underscore => {
+                write!(f, "`_` is not allowed as an identifier")
+            }
+            IdentError::InvalidChar { index, c } => write!(
+                f,
+                "invalid identifier character {:?} at byte index {}",
+                c, index
+            ),
+            IdentError::Keyword(keyword) => {
+                if ["Self", "crate", "self", "super"].contains(&&**keyword) {
+                    write!(
+                        f,
+                        "`{}` is a keyword and can't be used as an identifier",
+                        keyword
+                    )
+                } else {
+                    write!(
+                        f,
+                        "`{}` is a keyword; use the raw identifier `r#{}`",
+                        keyword, keyword
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdentError {}
+
+#[cfg(feature = "unicode")]
+fn is_ident_start(c: char) -> bool {
+    c == '_' || unicode_ident::is_xid_start(c)
+}
+
+#[cfg(feature = "unicode")]
+fn is_ident_continue(c: char) -> bool {
+    unicode_ident::is_xid_continue(c)
+}
+
+#[cfg(not(feature = "unicode"))]
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic()
+}
+
+#[cfg(not(feature = "unicode"))]
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
+
+// An IdentError will be returned if the input is not a legal Rust
+// identifier. The first character must be `_` or `XID_Start`, the rest
+// must be `XID_Continue`, and the input can't be a keyword.
+//
+// Without the `unicode` feature only ASCII identifiers are accepted.
+pub fn str_to_rust_ident(input: &str) -> Result<&str, IdentError> {
+    for (index, c) in input.char_indices() {
+        let valid = if index == 0 {
+            is_ident_start(c)
+        } else {
+            is_ident_continue(c)
+        };
+        if !valid {
+            return Err(IdentError::InvalidChar { index, c });
+        }
+    }
+
+    if input.is_empty() {
+        Err(IdentError::Empty)
+    } else if input == "_" {
+        Err(IdentError::Underscore)
+    } else if RUST_KEYWORDS.contains(&input) {
+        Err(IdentError::Keyword(input.to_string()))
+    } else {
+        Ok(input)
+    }
+}
+
+// Each UTF-16 code unit is written as two bytes in little-endian order,
+// with no byte order mark. For example "A" becomes `[0x41, 0x00]`.
+pub fn str_to_utf16_le_u8_vec(input: &str) -> Vec<u8> {
+    input.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+// Each UTF-16 code unit is written as two bytes in big-endian order,
+// with no byte order mark. For example "A" becomes `[0x00, 0x41]`.
+pub fn str_to_utf16_be_u8_vec(input: &str) -> Vec<u8> {
+    input.encode_utf16().flat_map(u16::to_be_bytes).collect()
+}
+
+// This conversion requires the `normalization` feature, which is also
+// enabled by `unicode`. Without it the function does not exist, rather
+// than silently skipping normalization.
+//
+// Returns the UTF-8 bytes of the input in Unicode Normalization Form C
+// (composed), so that text which may arrive composed or decomposed
+// hashes the same.
+#[cfg(feature = "normalization")]
+pub fn str_to_nfc_u8_vec(input: &str) -> Vec<u8> {
+    use unicode_normalization::UnicodeNormalization;
+
+    input.nfc().collect::<String>().into_bytes()
+}
+
+// This conversion requires the `normalization` feature, which is also
+// enabled by `unicode`. Without it the function does not exist, rather
+// than silently skipping normalization.
+//
+// Returns the UTF-8 bytes of the input in Unicode Normalization Form D
+// (decomposed).
+#[cfg(feature = "normalization")]
+pub fn str_to_nfd_u8_vec(input: &str) -> Vec<u8> {
+    use unicode_normalization::UnicodeNormalization;
+
+    input.nfd().collect::<String>().into_bytes()
+}
+
+// Error returned by the fixed-width field conversions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TooLongError {
+    // Length of the input in bytes.
+    pub len: usize,
+    // Maximum number of bytes that fit in the field.
+    pub max: usize,
+}
+
+impl fmt::Display for TooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "input is {} bytes, but the field only fits {}",
+            self.len, self.max
+        )
+    }
+}
+
+impl std::error::Error for TooLongError {}
+
+// Writes the UTF-8 bytes of the input followed by `pad` bytes up to
+// `width`, as for a fixed-size `char` array in a C struct. A
+// TooLongError will be returned if the input is longer than `width`
+// bytes.
+pub fn str_to_fixed_field(
+    input: &str,
+    width: usize,
+    pad: u8,
+) -> Result<Vec<u8>, TooLongError> {
+    if input.len() > width {
+        return Err(TooLongError {
+            len: input.len(),
+            max: width,
+        });
+    }
+    let mut out = Vec::with_capacity(width);
+    out.extend_from_slice(input.as_bytes());
+    out.resize(width, pad);
+    Ok(out)
+}
+
+// The same as `str_to_fixed_field`, but the last byte of the field is
+// always a nul terminator, so the input must be shorter than `width`
+// bytes. The result can be read back with `u8_slice_to_str_until_nul`
+// if `pad` is also nul.
+pub fn str_to_fixed_field_nul_terminated(
+    input: &str,
+    width: usize,
+    pad: u8,
+) -> Result<Vec<u8>, TooLongError> {
+    if input.len() >= width {
+        return Err(TooLongError {
+            len: input.len(),
+            max: width.saturating_sub(1),
+        });
+    }
+    let mut out = str_to_fixed_field(input, width - 1, pad)?;
+    out.push(0);
+    Ok(out)
+}
+
+// A NulError will be returned if the input contains any nul bytes.
+// Unlike `str_to_os_string`, this catches strings that would be cut
+// short when passed to the OS, for example as an argument to
+// `Command::arg`.
+pub fn str_to_argv_safe_os_string(input: &str) -> Result<OsString, NulError> {
+    match input.find('\0') {
+        Some(_) => Err(CString::new(input).unwrap_err()),
+        None => Ok(OsString::from(input)),
+    }
+}
+
+// Converts the input to UTF-8 bytes with every line ending replaced by
+// `ending`. On input `\r\n`, a lone `\n`, and a lone `\r` (as used by
+// classic Mac OS) are each treated as one line ending. With
+// `LineEnding::None` the line endings are removed.
+pub fn str_to_u8_vec_with_line_endings(
+    input: &str,
+    ending: LineEnding,
+) -> Vec<u8> {
+    let ending: &[u8] = match ending {
+        LineEnding::Lf => b"\n",
+        LineEnding::CrLf => b"\r\n",
+        LineEnding::None => b"",
+    };
+
+    let mut out = Vec::with_capacity(input.len());
+    let mut bytes = input.bytes().peekable();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\r' => {
+                bytes.next_if_eq(&b'\n');
+                out.extend_from_slice(ending);
+            }
+            b'\n' => out.extend_from_slice(ending),
+            b => out.push(b),
+        }
+    }
+    out
+}
+
+// Splits the input on `delimiter` and converts each piece to a
+// `CString`. An empty piece, such as between two adjacent delimiters,
+// becomes an empty `CString`, and an empty input gives one empty
+// `CString`.
+//
+// A NulError will be returned if any piece contains a nul byte.
+pub fn str_to_c_string_vec(
+    input: &str,
+    delimiter: char,
+) -> Result<Vec<CString>, NulError> {
+    input.split(delimiter).map(CString::new).collect()
+}
+
+// This never fails or allocates; the result is always borrowed, since
+// every `&str` is a valid `&OsStr`. It is the same as `str_to_os_str`,
+// for code that works with `Cow<OsStr>`.
+pub fn str_to_os_cow(input: &str) -> Cow<'_, OsStr> {
+    Cow::Borrowed(OsStr::new(input))
+}
+
+// This conversion requires the `smallvec` feature.
+//
+// Copies the input's bytes into a `SmallVec` that stores up to 32
+// bytes inline. Inputs of 32 bytes or less don't allocate; longer
+// inputs spill to the heap, the same as `str_to_u8_vec`.
+#[cfg(feature = "smallvec")]
+pub fn str_to_small_bytes(input: &str) -> smallvec::SmallVec<[u8; 32]> {
+    smallvec::SmallVec::from_slice(input.as_bytes())
+}
+
+// Returns the UTF-8 bytes of the input followed by nul bytes up to the
+// next multiple of `block`. Input that is already a multiple of
+// `block` long is not padded, so an empty input gives an empty vec
+// rather than a block of nuls.
+//
+// Panics if `block` is zero, in the same way as `<[T]>::chunks`.
+pub fn str_to_block_padded_u8_vec(input: &str, block: usize) -> Vec<u8> {
+    assert!(block != 0, "block size must be non-zero");
+
+    let len = input.len().next_multiple_of(block);
+    let mut out = Vec::with_capacity(len);
+    out.extend_from_slice(input.as_bytes());
+    out.resize(len, 0);
+    out
+}
+
+// Splits the input into slices of `chars_per_chunk` chars each; the
+// last slice may be shorter. Chars are never split, so each slice is
+// valid UTF-8 and borrows from the input. Note that a char is not
+// always what a user sees as one character; see `str_to_grapheme_vec`.
+// If `chars_per_chunk` is zero the result is empty.
+pub fn str_to_char_chunks(input: &str, chars_per_chunk: usize) -> Vec<&str> {
+    if chars_per_chunk == 0 {
+        return Vec::new();
+    }
+
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let end = rest
+            .char_indices()
+            .nth(chars_per_chunk)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    chunks
+}
+
+// Error returned by `str_to_latin1_u8_vec`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NonLatin1Error {
+    // Byte offset of the first character above U+00FF.
+    pub index: usize,
+    pub c: char,
+}
+
+impl fmt::Display for NonLatin1Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "character {:?} at byte index {} is not in Latin-1",
+            self.c, self.index
+        )
+    }
+}
+
+impl std::error::Error for NonLatin1Error {}
+
+// Encodes each character as the single byte with the same value, the
+// inverse of `u8_slice_to_latin1_string`. A NonLatin1Error will be
+// returned if the input contains any character above U+00FF.
+pub fn str_to_latin1_u8_vec(input: &str) -> Result<Vec<u8>, NonLatin1Error> {
+    input
+        .char_indices()
+        .map(|(index, c)| {
+            u8::try_from(c).map_err(|_| NonLatin1Error { index, c })
+        })
+        .collect()
+}
+
+// A character set that a string is expected to fit in, for
+// `str_to_u8_vec_checked`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Charset {
+    // U+0000 to U+007F.
+    Ascii,
+    // U+0000 to U+00FF.
+    Latin1,
+    // Any character.
+    Utf8,
+}
+
+impl Charset {
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Charset::Ascii => c.is_ascii(),
+            Charset::Latin1 => u32::from(c) <= 0xff,
+            Charset::Utf8 => true,
+        }
+    }
+}
+
+// Error returned by `str_to_u8_vec_checked`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnrepresentableError {
+    // Byte offset of the first character outside the charset.
+    pub index: usize,
+    pub c: char,
+    pub charset: Charset,
+}
+
+impl fmt::Display for UnrepresentableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "character {:?} at byte index {} is not in {:?}",
+            self.c, self.index, self.charset
+        )
+    }
+}
+
+impl std::error::Error for UnrepresentableError {}
+
+// Like `str_to_u8_vec`, but first checks that every character is in
+// `charset`. The output is always UTF-8; to encode as one byte per
+// character use `str_to_latin1_u8_vec` instead.
+//
+// An UnrepresentableError will be returned if the input contains a
+// character outside `charset`.
+pub fn str_to_u8_vec_checked(
+    input: &str,
+    charset: Charset,
+) -> Result<Vec<u8>, UnrepresentableError> {
+    match input.char_indices().find(|(_, c)| !charset.contains(*c)) {
+        Some((index, c)) => Err(UnrepresentableError { index, c, charset }),
+        None => Ok(input.as_bytes().to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_str_to_char_chunks() {
+        // Each of these chars is two bytes in UTF-8.
+        let input = "\u{e1}\u{e9}\u{ed}\u{f3}\u{fa}";
+        assert_eq!(
+            str_to_char_chunks(input, 2),
+            ["\u{e1}\u{e9}", "\u{ed}\u{f3}", "\u{fa}"]
+        );
+        assert_eq!(str_to_char_chunks(input, 5), [input]);
+        assert_eq!(str_to_char_chunks(input, 6), [input]);
+        assert_eq!(
+            str_to_char_chunks("a\u{1f600}b", 1),
+            ["a", "\u{1f600}", "b"]
+        );
+        assert!(str_to_char_chunks("", 2).is_empty());
+        assert!(str_to_char_chunks(input, 0).is_empty());
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_str_to_small_bytes_inline() {
+        let output = str_to_small_bytes("0123456789");
+        assert_eq!(output.as_slice(), b"0123456789");
+        assert!(!output.spilled());
+
+        assert!(str_to_small_bytes(&"a".repeat(33)).spilled());
+    }
+}
@@ -0,0 +1,57 @@
+use std::ffi::CString;
+use std::ffi::NulError;
+use std::os::raw::c_char;
+
+// A NulError will be returned if the input contains any nul bytes.
+//
+// Ownership of the string passes to the caller, typically to hand to C
+// code that takes ownership. The pointer must eventually be passed to
+// `from_raw_to_c_string` to free it. Freeing it with C's `free`, or any
+// allocator other than Rust's, is undefined behavior.
+pub fn string_to_c_string_into_raw(
+    input: String,
+) -> Result<*mut c_char, NulError> {
+    CString::new(input).map(CString::into_raw)
+}
+
+/// Takes back ownership of a pointer returned by
+/// `string_to_c_string_into_raw`, so that it is freed when the
+/// `CString` is dropped.
+///
+/// # Safety
+///
+/// `ptr` must have come from `string_to_c_string_into_raw` (or
+/// `CString::into_raw`) and must not have been reclaimed already. C code
+/// may have modified the bytes but must not have changed the length of
+/// the string, since the length is recomputed by scanning for the nul
+/// terminator and must match the original allocation.
+pub unsafe fn from_raw_to_c_string(ptr: *mut c_char) -> CString {
+    CString::from_raw(ptr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_string_raw_round_trip() {
+        let ptr = string_to_c_string_into_raw("abc".to_string()).unwrap();
+        // SAFETY: `ptr` came from `string_to_c_string_into_raw` and is
+        // reclaimed exactly once, so it is freed when `output` drops.
+        let output = unsafe { from_raw_to_c_string(ptr) };
+        assert_eq!(output.as_ptr(), ptr.cast_const());
+        assert_eq!(output.to_str(), Ok("abc"));
+
+        assert!(string_to_c_string_into_raw("a\0b".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_string_to_u8_vec_reuses_allocation() {
+        let input = String::with_capacity(16) + "abc";
+        let (ptr, capacity) = (input.as_ptr(), input.capacity());
+        let output = string_to_u8_vec(input);
+        assert_eq!(output, b"abc");
+        assert_eq!(output.as_ptr(), ptr);
+        assert_eq!(output.capacity(), capacity);
+    }
+}
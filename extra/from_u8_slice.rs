@@ -0,0 +1,681 @@
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::ffi::FromBytesWithNulError;
+use std::ffi::NulError;
+#[cfg(unix)]
+use std::ffi::OsString;
+use std::fmt;
+use std::os::unix::ffi::OsStringExt;
+use std::str::Utf8Error;
+use std::string::FromUtf16Error;
+
+// This conversion is only allowed on Unix.
+//
+// Splits a `KEY=VALUE` entry, such as one read from
+// `/proc/self/environ`, on the first `=`. Returns None if the input
+// does not contain an `=`.
+#[cfg(unix)]
+pub fn u8_slice_to_env_pair_unix(input: &[u8]) -> Option<(OsString, OsString)> {
+    let i = input.iter().position(|b| *b == b'=')?;
+    Some((
+        OsString::from_vec(input[..i].to_vec()),
+        OsString::from_vec(input[i + 1..].to_vec()),
+    ))
+}
+
+// Returns the text before the first nul byte, or the whole input if
+// there is no nul. Unlike the `CStr` conversions, the input does not
+// need to be nul-terminated, so this also reads fixed-size C fields
+// such as `char name[16]` that are only terminated if the text is
+// shorter than the field.
+pub fn u8_slice_to_str_until_nul(input: &[u8]) -> Result<&str, Utf8Error> {
+    let end = input.iter().position(|b| *b == 0).unwrap_or(input.len());
+    std::str::from_utf8(&input[..end])
+}
+
+// Skips a leading UTF-8 byte order mark (`EF BB BF`) if present, then
+// validates the rest. An input that is only a BOM gives an empty
+// string.
+pub fn u8_slice_to_str_strip_bom(input: &[u8]) -> Result<&str, Utf8Error> {
+    std::str::from_utf8(input.strip_prefix(b"\xef\xbb\xbf").unwrap_or(input))
+}
+
+// This never fails. Valid UTF-8 is copied as-is and each invalid byte
+// is written as `\xNN`. Unlike `u8_slice_to_string_lossy`, different
+// invalid inputs give different outputs. Note that a literal `\x` in
+// valid input is not escaped, so the output can't always be reversed.
+pub fn u8_slice_to_escaped_string(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len());
+    for chunk in input.utf8_chunks() {
+        out.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            out.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    out
+}
+
+// This never fails. A nul byte is appended to the input; any interior
+// nul bytes are kept, so C code will see the string end at the first
+// one.
+pub fn u8_slice_to_nul_terminated_u8_vec(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() + 1);
+    out.extend_from_slice(input);
+    out.push(0);
+    out
+}
+
+// A NulError will be returned if the input contains any nul bytes.
+pub fn u8_slice_to_nul_terminated_u8_vec_checked(
+    input: &[u8],
+) -> Result<Vec<u8>, NulError> {
+    CString::new(input).map(CString::into_bytes_with_nul)
+}
+
+// Error returned by `u8_slice_to_array` if the input length doesn't
+// match the array length.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WrongLengthError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for WrongLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "wrong length: expected {} bytes, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for WrongLengthError {}
+
+// A WrongLengthError will be returned if the input is not exactly `N`
+// bytes long. The bytes are copied into the array.
+pub fn u8_slice_to_array<const N: usize>(
+    input: &[u8],
+) -> Result<[u8; N], WrongLengthError> {
+    input.try_into().map_err(|_| WrongLengthError {
+        expected: N,
+        actual: input.len(),
+    })
+}
+
+// Error returned by `u8_slice_to_string_with_encoding`.
+#[cfg(feature = "encoding")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncodingError {
+    // The label does not name an encoding in the WHATWG Encoding
+    // Standard.
+    UnknownLabel(String),
+    // The input is not valid in the requested encoding.
+    Malformed { encoding: &'static str },
+}
+
+#[cfg(feature = "encoding")]
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::UnknownLabel(label) => {
+                write!(f, "unknown encoding label {:?}", label)
+            }
+            EncodingError::Malformed { encoding } => {
+                write!(f, "input is not valid {}", encoding)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl std::error::Error for EncodingError {}
+
+// This conversion requires the `encoding` feature.
+//
+// Decodes the input using the encoding named by `label`, for example
+// "windows-1252" or "shift_jis". Labels are matched as described in
+// the WHATWG Encoding Standard, so "latin1" means windows-1252. A byte
+// order mark is not treated specially.
+//
+// An EncodingError will be returned if the label is unknown or the
+// input is not valid in that encoding.
+#[cfg(feature = "encoding")]
+pub fn u8_slice_to_string_with_encoding(
+    input: &[u8],
+    label: &str,
+) -> Result<String, EncodingError> {
+    let encoding = encoding_rs::Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| EncodingError::UnknownLabel(label.to_string()))?;
+    encoding
+        .decode_without_bom_handling_and_without_replacement(input)
+        .map(|s| s.into_owned())
+        .ok_or(EncodingError::Malformed {
+            encoding: encoding.name(),
+        })
+}
+
+// This never fails. Invalid UTF-8 sequences will be replaced with "�",
+// then if the result is longer than `max_len` bytes it is cut at the
+// last char boundary that fits and "…" is appended. The ellipsis is not
+// counted towards `max_len`.
+pub fn u8_slice_to_truncated_string_lossy(
+    input: &[u8],
+    max_len: usize,
+) -> String {
+    let s = String::from_utf8_lossy(input);
+    if s.len() <= max_len {
+        return s.into_owned();
+    }
+
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &s[..end])
+}
+
+// Decodes WTF-8, as produced by `path_to_u8_vec_windows`, into UTF-16
+// code units. WTF-8 is UTF-8 extended so that unpaired surrogates are
+// encoded as three-byte sequences. Returns None if the input is not
+// valid WTF-8. As well as the rules of UTF-8, this means a lead
+// surrogate directly followed by a trail surrogate is rejected, since
+// that pair must be encoded as a single four-byte sequence.
+//
+// This is the platform-independent part of
+// `wtf8_u8_slice_to_path_buf_windows`.
+pub fn wtf8_u8_slice_to_u16_vec(input: &[u8]) -> Option<Vec<u16>> {
+    let mut units = Vec::with_capacity(input.len());
+    let mut prev_was_lead_surrogate = false;
+    let mut i = 0;
+    while i < input.len() {
+        let lead = input[i];
+        let len = match lead {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return None,
+        };
+        let seq = input.get(i..i + len)?;
+        if !seq[1..].iter().all(|b| b & 0xc0 == 0x80) {
+            return None;
+        }
+        let code_point = match len {
+            1 => u32::from(lead),
+            2 => (u32::from(lead & 0x1f) << 6) | u32::from(seq[1] & 0x3f),
+            3 => {
+                (u32::from(lead & 0x0f) << 12)
+                    | (u32::from(seq[1] & 0x3f) << 6)
+                    | u32::from(seq[2] & 0x3f)
+            }
+            _ => {
+                (u32::from(lead & 0x07) << 18)
+                    | (u32::from(seq[1] & 0x3f) << 12)
+                    | (u32::from(seq[2] & 0x3f) << 6)
+                    | u32::from(seq[3] & 0x3f)
+            }
+        };
+        // Reject overlong encodings and code points past the end of
+        // Unicode.
+        let min = [0, 0, 0x80, 0x800, 0x10000][len];
+        if code_point < min || code_point > 0x10ffff {
+            return None;
+        }
+        let is_trail_surrogate = (0xdc00..=0xdfff).contains(&code_point);
+        if prev_was_lead_surrogate && is_trail_surrogate {
+            return None;
+        }
+        prev_was_lead_surrogate = (0xd800..=0xdbff).contains(&code_point);
+
+        if code_point >= 0x10000 {
+            let c = code_point - 0x10000;
+            units.push(0xd800 | (c >> 10) as u16);
+            units.push(0xdc00 | (c & 0x3ff) as u16);
+        } else {
+            units.push(code_point as u16);
+        }
+        i += len;
+    }
+
+    Some(units)
+}
+
+// Error returned when decoding UTF-16 bytes.
+#[derive(Debug)]
+pub enum Utf16DecodeError {
+    // The input has an odd number of bytes, so it can't be split into
+    // code units.
+    OddLength(usize),
+    // The code units are not valid UTF-16.
+    Invalid(FromUtf16Error),
+}
+
+impl fmt::Display for Utf16DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Utf16DecodeError::OddLength(len) => {
+                write!(f, "UTF-16 input has odd length {}", len)
+            }
+            Utf16DecodeError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Utf16DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Utf16DecodeError::OddLength(_) => None,
+            Utf16DecodeError::Invalid(err) => Some(err),
+        }
+    }
+}
+
+// A Utf16DecodeError will be returned if the input has an odd length
+// or is not valid UTF-16LE. A byte order mark is not treated specially.
+pub fn u8_slice_to_string_utf16_le(
+    input: &[u8],
+) -> Result<String, Utf16DecodeError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(Utf16DecodeError::OddLength(input.len()));
+    }
+    let units = input
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16(&units).map_err(Utf16DecodeError::Invalid)
+}
+
+// A Utf16DecodeError will be returned if the input has an odd length
+// or is not valid UTF-16BE. A byte order mark is not treated specially.
+pub fn u8_slice_to_string_utf16_be(
+    input: &[u8],
+) -> Result<String, Utf16DecodeError> {
+    if !input.len().is_multiple_of(2) {
+        return Err(Utf16DecodeError::OddLength(input.len()));
+    }
+    let units = input
+        .chunks_exact(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16(&units).map_err(Utf16DecodeError::Invalid)
+}
+
+// Error returned by `u8_slice_split_utf8_prefix`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SplitUtf8Error {
+    // `len` is larger than the input.
+    TooShort { len: usize, actual: usize },
+    // The prefix is not valid UTF-8, or `len` splits a multi-byte char.
+    Utf8(Utf8Error),
+}
+
+impl fmt::Display for SplitUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplitUtf8Error::TooShort { len, actual } => write!(
+                f,
+                "prefix length {} is larger than input length {}",
+                len, actual
+            ),
+            SplitUtf8Error::Utf8(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SplitUtf8Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SplitUtf8Error::TooShort { .. } => None,
+            SplitUtf8Error::Utf8(err) => Some(err),
+        }
+    }
+}
+
+// Validates the first `len` bytes as UTF-8 and returns them along with
+// the rest of the input. Nothing is copied.
+//
+// A SplitUtf8Error will be returned if `len` is larger than the input,
+// or the prefix is not valid UTF-8 (which includes `len` falling in the
+// middle of a multi-byte char).
+pub fn u8_slice_split_utf8_prefix(
+    input: &[u8],
+    len: usize,
+) -> Result<(&str, &[u8]), SplitUtf8Error> {
+    if len > input.len() {
+        return Err(SplitUtf8Error::TooShort {
+            len,
+            actual: input.len(),
+        });
+    }
+    let (prefix, rest) = input.split_at(len);
+    let prefix = std::str::from_utf8(prefix).map_err(SplitUtf8Error::Utf8)?;
+    Ok((prefix, rest))
+}
+
+// Walks a block of nul-terminated strings, such as the contents of
+// `/proc/self/cmdline`, yielding each one as a `&CStr`. Nothing after
+// the final nul is yielded; if the block does not end with a nul the
+// last item is a FromBytesWithNulError.
+pub fn u8_slice_to_c_str_iter(
+    input: &[u8],
+) -> impl Iterator<Item = Result<&CStr, FromBytesWithNulError>> {
+    input
+        .split_inclusive(|b| *b == 0)
+        .map(CStr::from_bytes_with_nul)
+}
+
+// Error returned by `u8_slice_to_string_auto`.
+#[cfg(feature = "encoding")]
+#[derive(Debug)]
+pub enum DecodeError {
+    // The input had a UTF-8 BOM or no BOM, and is not valid UTF-8.
+    Utf8(Utf8Error),
+    // The input had a UTF-16 BOM, and is not valid UTF-16.
+    Utf16(Utf16DecodeError),
+}
+
+#[cfg(feature = "encoding")]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Utf8(err) => write!(f, "{}", err),
+            DecodeError::Utf16(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Utf8(err) => Some(err),
+            DecodeError::Utf16(err) => Some(err),
+        }
+    }
+}
+
+// This conversion requires the `encoding` feature.
+//
+// Picks the encoding from the input's byte order mark: UTF-8
+// (`EF BB BF`), UTF-16LE (`FF FE`) or UTF-16BE (`FE FF`). If there is
+// no BOM the input is assumed to be UTF-8. The BOM is not included in
+// the result.
+//
+// A DecodeError will be returned if the rest of the input is not valid
+// in the detected encoding.
+#[cfg(feature = "encoding")]
+pub fn u8_slice_to_string_auto(input: &[u8]) -> Result<String, DecodeError> {
+    let (encoding, bom_len) = encoding_rs::Encoding::for_bom(input)
+        .unwrap_or((encoding_rs::UTF_8, 0));
+    let rest = &input[bom_len..];
+
+    if encoding == encoding_rs::UTF_16LE {
+        u8_slice_to_string_utf16_le(rest).map_err(DecodeError::Utf16)
+    } else if encoding == encoding_rs::UTF_16BE {
+        u8_slice_to_string_utf16_be(rest).map_err(DecodeError::Utf16)
+    } else {
+        std::str::from_utf8(rest)
+            .map(str::to_string)
+            .map_err(DecodeError::Utf8)
+    }
+}
+
+// This never fails, but it loses data: invalid UTF-8 bytes are dropped
+// without leaving any trace in the output. Use
+// `u8_slice_to_string_lossy` instead to mark where bytes were lost
+// with "�".
+pub fn u8_slice_to_string_dropping_invalid(input: &[u8]) -> String {
+    input.utf8_chunks().map(|chunk| chunk.valid()).collect()
+}
+
+// Like `u8_slice_to_c_str`, but also returns the length of the string
+// in bytes including the nul terminator, so the caller doesn't need a
+// second pass to find it.
+//
+// A FromBytesWithNulError will be returned if the input is not
+// nul-terminated or contains any interior nul bytes.
+pub fn u8_slice_to_c_str_with_len(
+    input: &[u8],
+) -> Result<(&CStr, usize), FromBytesWithNulError> {
+    CStr::from_bytes_with_nul(input).map(|s| (s, input.len()))
+}
+
+// Error returned by `u8_slice_to_ascii_str`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NonAsciiByteError {
+    // Index of the first byte that is not ASCII.
+    pub index: usize,
+    pub byte: u8,
+}
+
+impl fmt::Display for NonAsciiByteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "non-ASCII byte 0x{:02x} at index {}",
+            self.byte, self.index
+        )
+    }
+}
+
+impl std::error::Error for NonAsciiByteError {}
+
+// An alternative to `u8_slice_to_str` for data that is expected
+// to be ASCII, which reports the first non-ASCII byte rather than a
+// UTF-8 error. The result borrows from the input.
+//
+// A NonAsciiByteError will be returned if any byte is 0x80 or above.
+pub fn u8_slice_to_ascii_str(input: &[u8]) -> Result<&str, NonAsciiByteError> {
+    match input.iter().position(|b| !b.is_ascii()) {
+        Some(index) => Err(NonAsciiByteError {
+            index,
+            byte: input[index],
+        }),
+        // ASCII is always valid UTF-8, so this can't fail.
+        None => Ok(std::str::from_utf8(input).expect("ASCII is valid UTF-8")),
+    }
+}
+
+// This never fails. Each byte is decoded as the Unicode code point
+// with the same value, which is the ISO-8859-1 (Latin-1) encoding.
+// Note that data labeled "latin1" on the web is usually windows-1252,
+// which differs in the range 0x80 to 0x9F; see
+// `u8_slice_to_string_with_encoding` for that.
+pub fn u8_slice_to_latin1_string(input: &[u8]) -> String {
+    input.iter().map(|b| char::from(*b)).collect()
+}
+
+// Parses a block of nul-separated `key=value` entries, such as
+// `key1=val1\0key2=val2\0\0`. Each entry is split on its first `=`, so
+// the value may contain `=`. The block ends at an empty entry (two
+// nuls in a row) or at the end of the input. Entries that have no `=`
+// or are not valid UTF-8 are skipped. The keys and values borrow from
+// the input.
+pub fn u8_slice_to_c_kv_pairs(input: &[u8]) -> Vec<(&str, &str)> {
+    input
+        .split(|b| *b == 0)
+        .take_while(|entry| !entry.is_empty())
+        .filter_map(|entry| std::str::from_utf8(entry).ok())
+        .filter_map(|entry| entry.split_once('='))
+        .collect()
+}
+
+// Like `u8_slice_to_str`, but returns a `Cow<str>` for code that also
+// handles owned strings. The result is always `Cow::Borrowed`; this
+// never allocates. Unlike `u8_slice_to_string_lossy`, invalid input is
+// an error rather than being replaced with "�".
+//
+// A Utf8Error will be returned if the input is not valid UTF-8.
+pub fn u8_slice_to_cow_str(input: &[u8]) -> Result<Cow<'_, str>, Utf8Error> {
+    std::str::from_utf8(input).map(Cow::Borrowed)
+}
+
+// A run of invalid UTF-8 found by `u8_slice_utf8_errors`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Utf8ErrorSpan<'a> {
+    // Index of the first invalid byte.
+    pub start: usize,
+    // Number of invalid bytes in the run.
+    pub len: usize,
+    // The invalid bytes, borrowed from the input.
+    pub bytes: &'a [u8],
+}
+
+// Finds every run of invalid UTF-8 in the input, where `from_utf8`
+// only reports the first. Adjacent invalid sequences are merged into
+// one span, so each span is a maximal run of bytes that would be
+// replaced by "�" in a lossy conversion. An incomplete sequence at the
+// end of the input counts as invalid. Valid input gives an empty vec.
+pub fn u8_slice_utf8_errors(input: &[u8]) -> Vec<Utf8ErrorSpan<'_>> {
+    let mut spans: Vec<Utf8ErrorSpan> = Vec::new();
+    let mut offset = 0;
+    for chunk in input.utf8_chunks() {
+        offset += chunk.valid().len();
+        let invalid = chunk.invalid();
+        if invalid.is_empty() {
+            continue;
+        }
+
+        match spans.last_mut() {
+            Some(last) if last.start + last.len == offset => {
+                last.len += invalid.len();
+                last.bytes = &input[last.start..last.start + last.len];
+            }
+            _ => spans.push(Utf8ErrorSpan {
+                start: offset,
+                len: invalid.len(),
+                bytes: invalid,
+            }),
+        }
+        offset += invalid.len();
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u8_slice_to_truncated_string_lossy() {
+        let cases: &[(&[u8], usize, &str)] = &[
+            (b"hi", 2, "hi"),
+            (b"hello", 2, "he\u{2026}"),
+            // The cut would fall inside the two bytes of "é".
+            ("h\u{e9}llo".as_bytes(), 2, "h\u{2026}"),
+            ("h\u{e9}llo".as_bytes(), 3, "h\u{e9}\u{2026}"),
+            // The cut would fall inside the three-byte replacement
+            // character.
+            (b"a\xff", 2, "a\u{2026}"),
+            (b"a\xff", 4, "a\u{fffd}"),
+            (b"abc", 0, "\u{2026}"),
+        ];
+        for (input, max_len, expected) in cases {
+            assert_eq!(
+                u8_slice_to_truncated_string_lossy(input, *max_len),
+                *expected,
+                "{:?} {}",
+                input,
+                max_len
+            );
+        }
+    }
+
+    #[test]
+    fn test_u8_slice_to_str_until_nul_fixed_field() {
+        assert_eq!(u8_slice_to_str_until_nul(b"ab\0\0"), Ok("ab"));
+        // A field filled to the end has no terminator.
+        assert_eq!(u8_slice_to_str_until_nul(b"abcd"), Ok("abcd"));
+    }
+
+    #[test]
+    fn test_wtf8_u8_slice_to_u16_vec() {
+        let cases: &[(&[u8], Option<&[u16]>)] = &[
+            (b"", Some(&[])),
+            (b"a", Some(&[0x61])),
+            ("\u{e9}".as_bytes(), Some(&[0xe9])),
+            // U+10000 is a surrogate pair in UTF-16.
+            (b"\xf0\x90\x80\x80", Some(&[0xd800, 0xdc00])),
+            // Unpaired lead and trail surrogates.
+            (b"\xed\xa0\x80", Some(&[0xd800])),
+            (b"\xed\xb0\x80", Some(&[0xdc00])),
+            (b"\xed\xa0\x80a\xed\xb0\x80", Some(&[0xd800, 0x61, 0xdc00])),
+            // A trail followed by a lead is still two unpaired
+            // surrogates.
+            (b"\xed\xb0\x80\xed\xa0\x80", Some(&[0xdc00, 0xd800])),
+            // A lead followed by a trail must be encoded as one
+            // four-byte sequence.
+            (b"\xed\xa0\x80\xed\xb0\x80", None),
+            // Overlong, truncated, and out of range sequences.
+            (b"\xc0\x80", None),
+            (b"\xe0\x80\x80", None),
+            (b"\xed\xa0", None),
+            (b"\xf4\x90\x80\x80", None),
+            (b"\x80", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                wtf8_u8_slice_to_u16_vec(input).as_deref(),
+                *expected,
+                "{:x?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_u8_slice_to_str_traced() {
+        let err = crate::traced::u8_slice_to_str_traced(b"a\xff").unwrap_err();
+        assert_eq!(err.from, "u8_slice");
+        assert_eq!(err.to, "str");
+        assert_eq!(err.function, "u8_slice_to_str");
+        assert_eq!(err.source.valid_up_to(), 1);
+        assert_eq!(
+            err.to_string(),
+            "u8_slice -> str: invalid utf-8 sequence of 1 bytes from index 1"
+        );
+    }
+
+    #[test]
+    fn test_u8_slice_to_ascii_str() {
+        assert_eq!(u8_slice_to_ascii_str(b"hello"), Ok("hello"));
+        assert_eq!(u8_slice_to_ascii_str(b""), Ok(""));
+        assert_eq!(
+            u8_slice_to_ascii_str("h\u{e9}llo".as_bytes()),
+            Err(NonAsciiByteError {
+                index: 1,
+                byte: 0xc3
+            })
+        );
+    }
+
+    #[test]
+    fn test_u8_slice_utf8_errors() {
+        let span = |start, bytes| Utf8ErrorSpan {
+            start,
+            len: <[u8]>::len(bytes),
+            bytes,
+        };
+
+        assert_eq!(u8_slice_utf8_errors(b"hello"), []);
+        assert_eq!(u8_slice_utf8_errors(b""), []);
+
+        // Two separate invalid runs.
+        assert_eq!(
+            u8_slice_utf8_errors(b"a\xffb\xfe\xfdc"),
+            [span(1, b"\xff"), span(3, b"\xfe\xfd")]
+        );
+
+        // A truncated sequence at the end.
+        assert_eq!(u8_slice_utf8_errors(b"ab\xe2\x82"), [span(2, b"\xe2\x82")]);
+
+        // Adjacent invalid sequences are merged into one span.
+        assert_eq!(
+            u8_slice_utf8_errors(b"\xff\xe2\x82a"),
+            [span(0, b"\xff\xe2\x82")]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+// A string slice that is guaranteed not to be empty. This is just a
+// wrapper around `&str`, so it is as cheap to pass around as the
+// slice itself.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NonEmptyStr<'a>(&'a str);
+
+// Error returned when converting an empty string to a `NonEmptyStr`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EmptyStrError;
+
+impl fmt::Display for EmptyStrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "string is empty")
+    }
+}
+
+impl std::error::Error for EmptyStrError {}
+
+impl<'a> TryFrom<&'a str> for NonEmptyStr<'a> {
+    type Error = EmptyStrError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        if input.is_empty() {
+            Err(EmptyStrError)
+        } else {
+            Ok(NonEmptyStr(input))
+        }
+    }
+}
+
+impl<'a> From<NonEmptyStr<'a>> for &'a str {
+    fn from(input: NonEmptyStr<'a>) -> Self {
+        input.0
+    }
+}
+
+impl Deref for NonEmptyStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for NonEmptyStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+// An EmptyStrError will be returned if the input is empty.
+pub fn str_to_non_empty_str(
+    input: &str,
+) -> Result<NonEmptyStr<'_>, EmptyStrError> {
+    NonEmptyStr::try_from(input)
+}
+
+pub fn non_empty_str_to_str(input: NonEmptyStr<'_>) -> &str {
+    input.0
+}
+
+pub fn non_empty_str_to_string(input: NonEmptyStr<'_>) -> String {
+    input.0.to_string()
+}
@@ -0,0 +1,166 @@
+// A registry of conversion functions that can be looked up at runtime
+// by their input and output types. This lets a tool treat its own
+// conversions the same way as the ones in this crate.
+//
+// Each function is stored as a `Box<dyn Fn(A) -> B>` inside a
+// `Box<dyn Any>`, keyed by the `TypeId`s of `A` and `B`. Since the key
+// determines the exact boxed type, looking a function up is just a
+// `downcast_ref`, which can't fail for a matching key; no unsafe code
+// is needed. The cost of this approach is that `A` and `B` must be
+// `'static`, so only conversions between owned types can be
+// registered.
+//
+// The key is the exact output type, so a fallible conversion is keyed
+// by its `Result`. For example `u8_vec_to_string` is found by asking
+// for `Vec<u8>` to `Result<String, FromUtf8Error>`; asking for
+// `Vec<u8>` to `String` returns None.
+
+use crate::from_c_string::c_string_to_box_c_str;
+#[cfg(unix)]
+use crate::from_c_string::c_string_to_os_string_unix;
+#[cfg(unix)]
+use crate::from_c_string::c_string_to_path_buf_unix;
+use crate::from_c_string::c_string_to_string;
+use crate::from_c_string::c_string_to_u8_vec;
+#[cfg(unix)]
+use crate::from_os_string::os_string_to_c_string_unix;
+use crate::from_os_string::os_string_to_path_buf;
+use crate::from_os_string::os_string_to_string;
+use crate::from_os_string::os_string_to_string_or_lossy;
+#[cfg(unix)]
+use crate::from_os_string::os_string_to_u8_vec_unix;
+#[cfg(unix)]
+use crate::from_path_buf::path_buf_to_c_string_unix;
+use crate::from_path_buf::path_buf_to_os_string;
+use crate::from_path_buf::path_buf_to_string;
+#[cfg(unix)]
+use crate::from_path_buf::path_buf_to_u8_vec_unix;
+use crate::from_string::string_to_c_string;
+use crate::from_string::string_to_os_string;
+use crate::from_string::string_to_u8_vec;
+use crate::from_u8_vec::u8_vec_to_c_string;
+#[cfg(unix)]
+use crate::from_u8_vec::u8_vec_to_os_string_unix;
+#[cfg(unix)]
+use crate::from_u8_vec::u8_vec_to_path_buf_unix;
+use crate::from_u8_vec::u8_vec_to_string;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+pub struct ConversionRegistry {
+    conversions: HashMap<(TypeId, TypeId), Box<dyn Any>>,
+}
+
+impl ConversionRegistry {
+    // Create a registry containing this crate's conversions between
+    // owned types. The `_unix` conversions are only included on Unix.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register(string_to_u8_vec);
+        registry.register(string_to_os_string);
+        registry.register(string_to_c_string);
+
+        registry.register(u8_vec_to_string);
+        #[cfg(unix)]
+        registry.register(u8_vec_to_path_buf_unix);
+        #[cfg(unix)]
+        registry.register(u8_vec_to_os_string_unix);
+        registry.register(u8_vec_to_c_string);
+
+        registry.register(path_buf_to_string);
+        #[cfg(unix)]
+        registry.register(path_buf_to_u8_vec_unix);
+        registry.register(path_buf_to_os_string);
+        #[cfg(unix)]
+        registry.register(path_buf_to_c_string_unix);
+
+        registry.register(os_string_to_string);
+        registry.register(os_string_to_string_or_lossy);
+        #[cfg(unix)]
+        registry.register(os_string_to_u8_vec_unix);
+        registry.register(os_string_to_path_buf);
+        #[cfg(unix)]
+        registry.register(os_string_to_c_string_unix);
+
+        registry.register(c_string_to_string);
+        registry.register(c_string_to_u8_vec);
+        #[cfg(unix)]
+        registry.register(c_string_to_path_buf_unix);
+        #[cfg(unix)]
+        registry.register(c_string_to_os_string_unix);
+        registry.register(c_string_to_box_c_str);
+
+        registry
+    }
+
+    // Create a registry with no conversions.
+    pub fn empty() -> Self {
+        Self {
+            conversions: HashMap::new(),
+        }
+    }
+
+    // Add a conversion from `A` to `B`, replacing any existing
+    // conversion between those types.
+    pub fn register<A, B, F>(&mut self, f: F)
+    where
+        A: 'static,
+        B: 'static,
+        F: Fn(A) -> B + 'static,
+    {
+        let f: Box<dyn Fn(A) -> B> = Box::new(f);
+        self.conversions
+            .insert((TypeId::of::<A>(), TypeId::of::<B>()), Box::new(f));
+    }
+
+    // Returns None if no conversion from `A` to `B` is registered.
+    pub fn get<A: 'static, B: 'static>(&self) -> Option<&dyn Fn(A) -> B> {
+        self.conversions
+            .get(&(TypeId::of::<A>(), TypeId::of::<B>()))
+            .and_then(|f| f.downcast_ref::<Box<dyn Fn(A) -> B>>())
+            .map(|f| &**f)
+    }
+
+    // Returns None if no conversion from `A` to `B` is registered.
+    pub fn convert<A: 'static, B: 'static>(&self, input: A) -> Option<B> {
+        self.get::<A, B>().map(|f| f(input))
+    }
+}
+
+impl Default for ConversionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::FromUtf8Error;
+
+    #[test]
+    fn test_register_and_get() {
+        let mut registry = ConversionRegistry::empty();
+        registry.register(|input: String| input.len());
+        let f = registry.get::<String, usize>().unwrap();
+        assert_eq!(f("abc".to_string()), 3);
+    }
+
+    #[test]
+    fn test_get_missing() {
+        let registry = ConversionRegistry::empty();
+        assert!(registry.get::<String, usize>().is_none());
+        assert_eq!(registry.convert::<String, usize>("abc".to_string()), None);
+    }
+
+    #[test]
+    fn test_fallible_keyed_by_result() {
+        let registry = ConversionRegistry::new();
+        let output = registry
+            .convert::<Vec<u8>, Result<String, FromUtf8Error>>(b"abc".to_vec())
+            .unwrap();
+        assert_eq!(output.unwrap(), "abc");
+        assert!(registry.get::<Vec<u8>, String>().is_none());
+    }
+}
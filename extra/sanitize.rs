@@ -0,0 +1,85 @@
+// Conversions from arbitrary text to strings that are safe to use in a
+// specific context. The strictest rules of any supported platform are
+// applied everywhere, so the output is portable.
+
+// Device names that Windows reserves in every directory, with or
+// without an extension.
+const RESERVED_FILE_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+    "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+];
+
+// This never fails. The result is a single file name that is valid on
+// Windows, macOS, and Linux:
+//
+// * Path separators, the characters `<>:"|?*`, and control characters
+//   are replaced with `_`.
+// * Trailing dots and spaces are removed, since Windows drops them.
+// * A reserved device name such as `CON` or `com1.txt` gets a leading
+//   `_`.
+// * An input that ends up empty (including "." and "..") becomes `_`.
+pub fn str_to_sanitized_file_name_string(input: &str) -> String {
+    let mut out = input
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+
+    let trimmed_len = out.trim_end_matches(['.', ' ']).len();
+    out.truncate(trimmed_len);
+
+    if out.is_empty() {
+        return "_".to_string();
+    }
+
+    let stem = out.split('.').next().unwrap_or_default();
+    if RESERVED_FILE_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem.trim_end()))
+    {
+        out.insert(0, '_');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_str_to_sanitized_file_name_string() {
+        let cases = &[
+            ("report.txt", "report.txt"),
+            // Reserved device names, in any case and with or without
+            // an extension.
+            ("CON", "_CON"),
+            ("nul.txt", "_nul.txt"),
+            ("Com1.tar.gz", "_Com1.tar.gz"),
+            ("CONSOLE", "CONSOLE"),
+            // Trailing dots and spaces.
+            ("name. . ", "name"),
+            ("...", "_"),
+            (".", "_"),
+            ("..", "_"),
+            ("", "_"),
+            // Separators and control characters, including nul.
+            ("a/b\\c", "a_b_c"),
+            ("a\0b", "a_b"),
+            ("a<b>:\"|?*", "a_b______"),
+            ("tab\there", "tab_here"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                str_to_sanitized_file_name_string(input),
+                *expected,
+                "{:?}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// Errors from a conversion, annotated with which conversion failed.
+// When a conversion deep inside a chain of calls fails, the underlying
+// error often doesn't say enough to find it; a `Traced` error names the
+// types and the function as well.
+//
+// The `_traced` functions below are generated, one for each fallible
+// generated conversion in the `from_*` modules. The hand-written
+// conversions in those modules don't have `_traced` variants.
+
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Traced<E> {
+    // Short name of the input type, such as "u8_slice".
+    pub from: &'static str,
+    // Short name of the output type, such as "str".
+    pub to: &'static str,
+    // Name of the conversion function that failed.
+    pub function: &'static str,
+    // The underlying error.
+    pub source: E,
+}
+
+impl<E> Traced<E> {
+    pub fn new(
+        from: &'static str,
+        to: &'static str,
+        function: &'static str,
+        source: E,
+    ) -> Self {
+        Traced {
+            from,
+            to,
+            function,
+            source,
+        }
+    }
+}
+
+// Formats as "<from> -> <to>: <source>", for example
+// "u8_slice -> str: invalid utf-8 sequence of 1 bytes from index 3".
+impl<E: fmt::Display> fmt::Display for Traced<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {}: {}", self.from, self.to, self.source)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Traced<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
@@ -0,0 +1,159 @@
+// Storage for many C strings in one buffer, so that converting a large
+// number of strings to C strings doesn't allocate once per string.
+//
+// Strings are added to a `CStringArena` with `push`, which returns an
+// index. A `&CStr` can't be handed out while the arena is still
+// growing, since the buffer may move when it reallocates. Once all the
+// strings have been added, `seal` turns the arena into a
+// `SealedCStringArena`, which can no longer grow and so can lend out
+// `&CStr`s by index.
+//
+// The buffer grows by doubling, so adding N strings causes O(log N)
+// allocations rather than N.
+
+use std::ffi::CStr;
+use std::ffi::NulError;
+
+#[derive(Clone, Debug, Default)]
+pub struct CStringArena {
+    // Every string followed by its nul terminator, back to back.
+    bytes: Vec<u8>,
+    // Offset of the start of each string in `bytes`.
+    starts: Vec<usize>,
+}
+
+impl CStringArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Creates an empty arena that can hold `bytes` bytes of strings,
+    // including their nul terminators, before reallocating.
+    pub fn with_capacity(bytes: usize) -> Self {
+        CStringArena {
+            bytes: Vec::with_capacity(bytes),
+            starts: Vec::new(),
+        }
+    }
+
+    // Copies the input and a nul terminator into the arena. Returns
+    // the index to pass to `SealedCStringArena::get`; indices count up
+    // from zero in the order strings were added.
+    //
+    // A NulError will be returned if the input contains any nul
+    // bytes. The arena is not changed in that case.
+    pub fn push(&mut self, input: &str) -> Result<usize, NulError> {
+        if input.contains('\0') {
+            // `CString::new` is the only way to construct a NulError.
+            return Err(std::ffi::CString::new(input).unwrap_err());
+        }
+
+        self.starts.push(self.bytes.len());
+        self.bytes.extend_from_slice(input.as_bytes());
+        self.bytes.push(0);
+        Ok(self.starts.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    // Stops the arena from growing so that its strings can be
+    // borrowed.
+    pub fn seal(self) -> SealedCStringArena {
+        SealedCStringArena {
+            bytes: self.bytes,
+            starts: self.starts,
+        }
+    }
+}
+
+// A `CStringArena` that has been sealed. See the module comment.
+#[derive(Clone, Debug)]
+pub struct SealedCStringArena {
+    bytes: Vec<u8>,
+    starts: Vec<usize>,
+}
+
+impl SealedCStringArena {
+    // Returns the string added with the given index, or None if the
+    // index is out of range. The result borrows from the arena.
+    pub fn get(&self, index: usize) -> Option<&CStr> {
+        let start = *self.starts.get(index)?;
+        let end = self
+            .starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.bytes.len());
+        // The range is one string and its terminator, so this can't
+        // fail.
+        Some(
+            CStr::from_bytes_with_nul(&self.bytes[start..end])
+                .expect("arena entry is nul-terminated"),
+        )
+    }
+
+    // Iterates over the strings in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &CStr> {
+        (0..self.len()).filter_map(move |i| self.get(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+}
+
+// Adds each string to a new arena and seals it. A NulError will be
+// returned if any string contains a nul byte.
+pub fn str_iter_to_sealed_c_string_arena<'a>(
+    items: impl IntoIterator<Item = &'a str>,
+) -> Result<SealedCStringArena, NulError> {
+    let mut arena = CStringArena::new();
+    for item in items {
+        arena.push(item)?;
+    }
+    Ok(arena.seal())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_seal_get() {
+        let items = ["", "a", "hello", "\u{e9}"];
+        let mut arena = CStringArena::new();
+        for (i, item) in items.iter().enumerate() {
+            assert_eq!(arena.push(item), Ok(i));
+        }
+        assert_eq!(arena.len(), items.len());
+
+        let arena = arena.seal();
+        for (i, item) in items.iter().enumerate() {
+            assert_eq!(arena.get(i).unwrap().to_str(), Ok(*item));
+        }
+        assert_eq!(arena.get(items.len()), None);
+        assert_eq!(arena.iter().count(), items.len());
+    }
+
+    #[test]
+    fn test_push_interior_nul() {
+        let mut arena = CStringArena::new();
+        arena.push("a").unwrap();
+        let err = arena.push("b\0c").unwrap_err();
+        assert_eq!(err.nul_position(), 1);
+        // The arena is not changed.
+        assert_eq!(arena.len(), 1);
+        assert_eq!(arena.seal().get(0).unwrap().to_bytes(), b"a");
+
+        assert!(str_iter_to_sealed_c_string_arena(["a", "\0"]).is_err());
+    }
+}
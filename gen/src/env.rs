@@ -0,0 +1,54 @@
+// Conversions for building the environment of a child process, as
+// passed to `execve` in its `envp` argument.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ffi::NulError;
+use std::fmt;
+
+// Error returned by `env_map_to_c_string_vec` when a key or value
+// contains a nul byte.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvNulError {
+    // The key of the entry that couldn't be converted.
+    pub key: String,
+    pub source: NulError,
+}
+
+impl fmt::Display for EnvNulError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "environment variable {:?} contains a nul byte", self.key)
+    }
+}
+
+impl std::error::Error for EnvNulError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// Converts each entry to a `KEY=VALUE` C string. The output is sorted
+// by key, so it doesn't depend on the map's iteration order. Keys are
+// not checked for `=`, which would make the entry ambiguous.
+//
+// An EnvNulError will be returned if any key or value contains a nul
+// byte. If there are several, the first key in sorted order is
+// reported.
+pub fn env_map_to_c_string_vec(
+    input: &HashMap<String, String>,
+) -> Result<Vec<CString>, EnvNulError> {
+    let mut entries = input.iter().collect::<Vec<_>>();
+    entries.sort();
+
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            CString::new(format!("{}={}", key, value)).map_err(|source| {
+                EnvNulError {
+                    key: key.clone(),
+                    source,
+                }
+            })
+        })
+        .collect()
+}
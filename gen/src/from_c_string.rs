@@ -1,7 +1,10 @@
 use std::ffi::IntoStringError;
 use std::ffi::{CStr, CString};
+#[cfg(unix)]
 use std::ffi::{OsStr, OsString};
+#[cfg(unix)]
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
+#[cfg(unix)]
 use std::path::{Path, PathBuf};
 use std::str::Utf8Error;
 
@@ -17,26 +20,38 @@ pub fn c_string_to_u8_slice(input: &CString) -> &[u8] {
     input.as_bytes()
 }
 
+// This reuses the input's allocation. The nul terminator is dropped and no
+// bytes are copied.
 pub fn c_string_to_u8_vec(input: CString) -> Vec<u8> {
     input.into_bytes()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn c_string_to_path_unix(input: &CString) -> &Path {
     Path::new(OsStr::from_bytes(input.as_bytes()))
 }
 
 // This conversion is only allowed on Unix.
+//
+// This reuses the input's allocation. The nul terminator is dropped and no
+// bytes are copied.
+#[cfg(unix)]
 pub fn c_string_to_path_buf_unix(input: CString) -> PathBuf {
     PathBuf::from(OsString::from_vec(input.into_bytes()))
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn c_string_to_os_str_unix(input: &CString) -> &OsStr {
     OsStr::from_bytes(input.as_bytes())
 }
 
 // This conversion is only allowed on Unix.
+//
+// This reuses the input's allocation. The nul terminator is dropped and no
+// bytes are copied.
+#[cfg(unix)]
 pub fn c_string_to_os_string_unix(input: CString) -> OsString {
     OsString::from_vec(input.into_bytes())
 }
@@ -44,3 +59,25 @@ pub fn c_string_to_os_string_unix(input: CString) -> OsString {
 pub fn c_string_to_c_str(input: &CString) -> &CStr {
     input.as_c_str()
 }
+
+// This never fails and reuses the allocation. A `Box<CStr>` is smaller
+// than a `CString` since it has no capacity, which helps when storing
+// many strings.
+pub fn c_string_to_box_c_str(input: CString) -> Box<CStr> {
+    input.into_boxed_c_str()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn test_c_string_to_path_buf_unix_reuses_allocation() {
+        let input = CString::new("a/b.txt").unwrap();
+        let ptr = input.as_ptr().cast::<u8>();
+        let output = c_string_to_path_buf_unix(input);
+        assert_eq!(output.as_os_str().as_bytes(), b"a/b.txt");
+        assert_eq!(output.as_os_str().as_bytes().as_ptr(), ptr);
+    }
+}
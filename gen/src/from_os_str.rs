@@ -1,8 +1,14 @@
+use std::env::JoinPathsError;
+#[cfg(unix)]
 use std::ffi::FromBytesWithNulError;
+#[cfg(unix)]
 use std::ffi::NulError;
+#[cfg(unix)]
 use std::ffi::{CStr, CString};
 use std::ffi::{OsStr, OsString};
-use std::os::unix::ffi::OsStrExt;
+use std::fmt;
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::path::{Path, PathBuf};
 
 // Returns None if the input is not valid UTF-8.
@@ -16,11 +22,13 @@ pub fn os_str_to_string(input: &OsStr) -> Option<String> {
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn os_str_to_u8_slice_unix(input: &OsStr) -> &[u8] {
     input.as_bytes()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn os_str_to_u8_vec_unix(input: &OsStr) -> Vec<u8> {
     input.as_bytes().to_vec()
 }
@@ -43,6 +51,7 @@ pub fn os_str_to_os_string(input: &OsStr) -> OsString {
 // terminated or contains any interior nul bytes. If your input is not nul-
 // terminated then a conversion without allocation is not possible, convert
 // to a CString instead.
+#[cfg(unix)]
 pub fn os_str_to_c_str_unix(
     input: &OsStr,
 ) -> Result<&CStr, FromBytesWithNulError> {
@@ -52,6 +61,320 @@ pub fn os_str_to_c_str_unix(
 // This conversion is only allowed on Unix.
 //
 // A NulError will be returned if the input contains any nul bytes.
+#[cfg(unix)]
 pub fn os_str_to_c_string_unix(input: &OsStr) -> Result<CString, NulError> {
     CString::new(input.as_bytes())
 }
+
+// This conversion requires the `normalization` feature.
+//
+// Invalid UTF-8 sequences will be replaced with "�", then the result
+// is converted to Unicode Normalization Form C (composed). Linux
+// filesystems usually store names in this form.
+#[cfg(feature = "normalization")]
+pub fn os_str_to_nfc_string_lossy(input: &OsStr) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    input.to_string_lossy().nfc().collect()
+}
+
+// This conversion requires the `normalization` feature.
+//
+// Invalid UTF-8 sequences will be replaced with "�", then the result
+// is converted to Unicode Normalization Form D (decomposed). macOS
+// filesystems usually store names in this form.
+#[cfg(feature = "normalization")]
+pub fn os_str_to_nfd_string_lossy(input: &OsStr) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    input.to_string_lossy().nfd().collect()
+}
+
+// Returns true if the input is valid UTF-8. This does not allocate.
+pub fn os_str_is_utf8(input: &OsStr) -> bool {
+    input.to_str().is_some()
+}
+
+// Quotes the input for a POSIX shell by wrapping it in single quotes.
+// Each embedded `'` is written as `'\''`. Invalid UTF-8 sequences will
+// be replaced with "�", so for non-UTF-8 input the quoted string does
+// not name the original file; use it for display and logging.
+pub fn os_str_to_shell_quoted_string_lossy(input: &OsStr) -> String {
+    format!("'{}'", input.to_string_lossy().replace('\'', "'\\''"))
+}
+
+// This conversion is only allowed on Unix.
+//
+// This never fails. Printable ASCII (including space) is copied as-is,
+// while `%` and every other byte is written as `%XX`. The result is
+// pure ASCII and can be turned back into the original with
+// `percent_encoded_str_to_os_string_unix`.
+#[cfg(unix)]
+pub fn os_str_to_percent_encoded_string_unix(input: &OsStr) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.as_bytes() {
+        if (b' '..=b'~').contains(byte) && *byte != b'%' {
+            out.push(char::from(*byte));
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+// Error returned by `percent_encoded_str_to_os_string_unix` when a `%`
+// is not followed by two hex digits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PercentDecodeError {
+    // Byte index of the `%`.
+    pub index: usize,
+}
+
+impl fmt::Display for PercentDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid percent escape at byte index {}", self.index)
+    }
+}
+
+impl std::error::Error for PercentDecodeError {}
+
+// This conversion is only allowed on Unix.
+//
+// The inverse of `os_str_to_percent_encoded_string_unix`. Each `%XX`
+// is decoded to a raw byte, so the output does not have to be valid
+// UTF-8. A PercentDecodeError will be returned if a `%` is not followed
+// by two hex digits.
+#[cfg(unix)]
+pub fn percent_encoded_str_to_os_string_unix(
+    input: &str,
+) -> Result<OsString, PercentDecodeError> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = input
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(PercentDecodeError { index: i })?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(OsString::from_vec(out))
+}
+
+// Splits a `PATH`-like variable on the platform separator (`:` on Unix,
+// `;` on Windows) using the same rules as `std::env::split_paths`.
+// Empty entries are kept as empty strings.
+pub fn os_str_to_path_entries(input: &OsStr) -> Vec<OsString> {
+    std::env::split_paths(input)
+        .map(PathBuf::into_os_string)
+        .collect()
+}
+
+// The inverse of `os_str_to_path_entries`. A JoinPathsError will be
+// returned if any entry contains the separator (or, on Windows, a `"`).
+pub fn path_entries_to_os_string(
+    input: &[OsString],
+) -> Result<OsString, JoinPathsError> {
+    std::env::join_paths(input)
+}
+
+// Invalid UTF-8 sequences will be replaced with "�", then control
+// characters are escaped so that printing the result can't move the
+// cursor or start a terminal escape sequence. C0 controls and DEL use
+// caret notation (ESC becomes `^[`, DEL becomes `^?`), and C1 controls
+// are written as `\xNN`. All other characters are copied as-is.
+pub fn os_str_to_terminal_safe_string_lossy(input: &OsStr) -> String {
+    let mut out = String::new();
+    for c in input.to_string_lossy().chars() {
+        match u32::from(c) {
+            code @ 0x00..=0x1f => {
+                out.push('^');
+                out.push(char::from(code as u8 + 0x40));
+            }
+            0x7f => out.push_str("^?"),
+            code @ 0x80..=0x9f => out.push_str(&format!("\\x{:02x}", code)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// This conversion is only allowed on Unix.
+//
+// This never fails. Valid UTF-8 is copied as-is, and each maximal run
+// of invalid bytes is passed to `f` and replaced with what it returns.
+// For example `f` can return "�" to match `to_string_lossy`, a `\xNN`
+// escape for each byte, or an empty string to drop the bytes.
+#[cfg(unix)]
+pub fn os_str_to_string_map_invalid_unix(
+    input: &OsStr,
+    f: impl Fn(&[u8]) -> String,
+) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(bytes.len());
+    let mut invalid_start = 0;
+    let mut invalid_end = 0;
+
+    for chunk in bytes.utf8_chunks() {
+        if !chunk.valid().is_empty() {
+            if invalid_start != invalid_end {
+                out.push_str(&f(&bytes[invalid_start..invalid_end]));
+            }
+            out.push_str(chunk.valid());
+            invalid_start = invalid_end + chunk.valid().len();
+            invalid_end = invalid_start;
+        }
+        invalid_end += chunk.invalid().len();
+    }
+    if invalid_start != invalid_end {
+        out.push_str(&f(&bytes[invalid_start..invalid_end]));
+    }
+
+    out
+}
+
+// Error returned by `os_str_to_str_or_reason` when the input is not
+// valid UTF-8.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OsStrNonUtf8 {
+    // The input with invalid sequences replaced by "�", for use in
+    // error messages.
+    pub lossy: String,
+}
+
+impl fmt::Display for OsStrNonUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is not valid UTF-8", self.lossy)
+    }
+}
+
+impl std::error::Error for OsStrNonUtf8 {}
+
+// Like `os_str_to_str`, but returns an error that can be propagated
+// with `?` instead of None. Valid input is borrowed without allocating;
+// only the error case allocates.
+pub fn os_str_to_str_or_reason(input: &OsStr) -> Result<&str, OsStrNonUtf8> {
+    input.to_str().ok_or_else(|| OsStrNonUtf8 {
+        lossy: input.to_string_lossy().into_owned(),
+    })
+}
+
+// This conversion is only allowed on Windows.
+//
+// Returns the UTF-16 code units followed by a nul terminator, as
+// expected by Win32 functions that take an `LPCWSTR`. Any interior nul
+// is kept, so Windows will see the string end early; use
+// `os_str_to_wide_nul_vec_checked_windows` to reject that case.
+#[cfg(windows)]
+pub fn os_str_to_wide_nul_vec_windows(input: &OsStr) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+
+    input.encode_wide().chain(Some(0)).collect()
+}
+
+// Error returned by `os_str_to_wide_nul_vec_checked_windows`.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WideNulError {
+    // Index, in UTF-16 code units, of the first nul.
+    pub index: usize,
+}
+
+#[cfg(windows)]
+impl fmt::Display for WideNulError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "nul code unit found at index {}", self.index)
+    }
+}
+
+#[cfg(windows)]
+impl std::error::Error for WideNulError {}
+
+// This conversion is only allowed on Windows.
+//
+// Like `os_str_to_wide_nul_vec_windows`, but a WideNulError will be
+// returned if the input contains any nul code units.
+#[cfg(windows)]
+pub fn os_str_to_wide_nul_vec_checked_windows(
+    input: &OsStr,
+) -> Result<Vec<u16>, WideNulError> {
+    let out = os_str_to_wide_nul_vec_windows(input);
+    match out.iter().position(|u| *u == 0) {
+        Some(index) if index != out.len() - 1 => Err(WideNulError { index }),
+        _ => Ok(out),
+    }
+}
+
+// Error returned by `os_str_to_path_with_extension`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WrongExtensionError {
+    // The extension that was required.
+    pub expected: String,
+    // The path's actual extension, converted lossily, or None if it
+    // has no extension.
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for WrongExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "expected extension {:?}, found {:?}",
+                self.expected, actual
+            ),
+            None => write!(
+                f,
+                "expected extension {:?}, found no extension",
+                self.expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WrongExtensionError {}
+
+// Like `os_str_to_path`, but checks that the path's extension is
+// `ext`, which is given without the leading dot. The comparison
+// ignores ASCII case, so "a.TXT" matches "txt", but other letters must
+// match exactly. Only the last extension is checked.
+//
+// A WrongExtensionError will be returned if the extension is missing
+// or different.
+pub fn os_str_to_path_with_extension<'a>(
+    input: &'a OsStr,
+    ext: &str,
+) -> Result<&'a Path, WrongExtensionError> {
+    let path = Path::new(input);
+    match path.extension() {
+        Some(actual) if actual.eq_ignore_ascii_case(ext) => Ok(path),
+        actual => Err(WrongExtensionError {
+            expected: ext.to_string(),
+            actual: actual.map(|a| a.to_string_lossy().into_owned()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_os_str_is_utf8() {
+        assert!(os_str_is_utf8(OsStr::new("abc")));
+        assert!(os_str_is_utf8(OsStr::new("")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_os_str_is_utf8_unix() {
+        assert!(!os_str_is_utf8(OsStr::from_bytes(&[0xff])));
+    }
+}
@@ -1,7 +1,13 @@
+use crate::encoding::Base32Error;
+#[cfg(unix)]
+use std::ffi::CStr;
+use std::ffi::CString;
+#[cfg(unix)]
 use std::ffi::FromBytesWithNulError;
 use std::ffi::NulError;
-use std::ffi::{CStr, CString};
 use std::ffi::{OsStr, OsString};
+use std::fmt;
+#[cfg(unix)]
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::path::{Path, PathBuf};
 
@@ -15,11 +21,13 @@ pub fn os_string_to_string(input: OsString) -> Result<String, OsString> {
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn os_string_to_u8_slice_unix(input: &OsString) -> &[u8] {
     input.as_bytes()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn os_string_to_u8_vec_unix(input: OsString) -> Vec<u8> {
     input.into_vec()
 }
@@ -42,6 +50,7 @@ pub fn os_string_to_os_str(input: &OsString) -> &OsStr {
 // terminated or contains any interior nul bytes. If your input is not nul-
 // terminated then a conversion without allocation is not possible, convert
 // to a CString instead.
+#[cfg(unix)]
 pub fn os_string_to_c_str_unix(
     input: &OsString,
 ) -> Result<&CStr, FromBytesWithNulError> {
@@ -51,8 +60,260 @@ pub fn os_string_to_c_str_unix(
 // This conversion is only allowed on Unix.
 //
 // A NulError will be returned if the input contains any nul bytes.
+#[cfg(unix)]
 pub fn os_string_to_c_string_unix(
     input: OsString,
 ) -> Result<CString, NulError> {
     CString::new(input.into_vec())
 }
+
+// This never fails. If the input is valid UTF-8 it is returned without
+// copying, otherwise invalid sequences will be replaced with "�".
+pub fn os_string_to_string_or_lossy(input: OsString) -> String {
+    input
+        .into_string()
+        .unwrap_or_else(|s| s.to_string_lossy().into_owned())
+}
+
+// This conversion is only allowed on Windows.
+//
+// Returns the raw UTF-16 code units without a nul terminator. Unpaired
+// surrogates are preserved, so unlike a conversion through UTF-8 this
+// never loses information.
+#[cfg(windows)]
+pub fn os_string_to_u16_vec_windows(input: &OsString) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+
+    input.encode_wide().collect()
+}
+
+// This conversion is only allowed on Windows.
+//
+// The inverse of `os_string_to_u16_vec_windows`. This never fails;
+// unpaired surrogates in the input are preserved.
+#[cfg(windows)]
+pub fn u16_vec_to_os_string_windows(input: Vec<u16>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+
+    OsString::from_wide(&input)
+}
+
+// This conversion is only allowed on Windows.
+//
+// Windows strings are not stored as bytes, so the input is converted
+// to UTF-8 first. Unpaired surrogates can't be represented in UTF-8
+// and will be replaced with "�", as with
+// `os_string_to_string_or_lossy`.
+//
+// A NulError will be returned if the input contains any nul
+// characters.
+#[cfg(windows)]
+pub fn os_string_to_c_string_windows(
+    input: OsString,
+) -> Result<CString, NulError> {
+    CString::new(os_string_to_string_or_lossy(input))
+}
+
+// Error returned by `portable_str_to_os_string`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PortableStringError {
+    // The input doesn't start with a known tag.
+    UnknownTag,
+    // The tag is valid but was written on another platform, so the
+    // data can't be turned into an `OsString` here.
+    WrongPlatform,
+    // The data after a `b:` or `w:` tag is not valid base32.
+    Base32(Base32Error),
+    // The data after a `w:` tag has an odd number of bytes, so it
+    // can't be split into UTF-16 code units.
+    OddLength(usize),
+}
+
+impl fmt::Display for PortableStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PortableStringError::UnknownTag => {
+                write!(f, "portable string has an unknown tag")
+            }
+            PortableStringError::WrongPlatform => {
+                write!(f, "portable string was written on a different platform")
+            }
+            PortableStringError::Base32(err) => write!(f, "{}", err),
+            PortableStringError::OddLength(len) => {
+                write!(f, "UTF-16 data has odd length {}", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PortableStringError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PortableStringError::Base32(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// This never fails. Returns a string that can be stored as text and
+// turned back into exactly the same `OsString` with
+// `portable_str_to_os_string`. The format is a tag followed by data:
+//
+// * `u:` followed by the string itself, if it is valid UTF-8.
+// * `b:` followed by the raw bytes in base32 (RFC 4648 with padding).
+//   This is only written on Unix.
+// * `w:` followed by the UTF-16LE bytes of the wide string in base32.
+//   This is only written on Windows.
+//
+// Valid UTF-8 is always written with `u:`, so the common case stays
+// readable and can be decoded on any platform.
+pub fn os_string_to_portable_string(input: &OsString) -> String {
+    if let Some(s) = input.to_str() {
+        return format!("u:{}", s);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        format!(
+            "b:{}",
+            crate::encoding::u8_slice_to_base32_string(input.as_bytes())
+        )
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        let bytes = input
+            .encode_wide()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        format!("w:{}", crate::encoding::u8_slice_to_base32_string(&bytes))
+    }
+}
+
+// The inverse of `os_string_to_portable_string`.
+//
+// A PortableStringError will be returned if the tag is unknown, the
+// base32 data is invalid, or the string was written on another
+// platform with data that can't be represented here (`b:` on Windows
+// or `w:` on Unix).
+pub fn portable_str_to_os_string(
+    input: &str,
+) -> Result<OsString, PortableStringError> {
+    if let Some(s) = input.strip_prefix("u:") {
+        return Ok(OsString::from(s));
+    }
+
+    if let Some(data) = input.strip_prefix("b:") {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+
+            let bytes = crate::encoding::base32_str_to_u8_vec(data)
+                .map_err(PortableStringError::Base32)?;
+            return Ok(OsString::from_vec(bytes));
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = data;
+            return Err(PortableStringError::WrongPlatform);
+        }
+    }
+
+    if let Some(data) = input.strip_prefix("w:") {
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStringExt;
+
+            let bytes = crate::encoding::base32_str_to_u8_vec(data)
+                .map_err(PortableStringError::Base32)?;
+            if !bytes.len().is_multiple_of(2) {
+                return Err(PortableStringError::OddLength(bytes.len()));
+            }
+            let units = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>();
+            return Ok(OsString::from_wide(&units));
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = data;
+            return Err(PortableStringError::WrongPlatform);
+        }
+    }
+
+    Err(PortableStringError::UnknownTag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_string_utf8() {
+        let input = OsString::from("h\u{e9}llo");
+        let portable = os_string_to_portable_string(&input);
+        assert_eq!(portable, "u:h\u{e9}llo");
+        assert_eq!(portable_str_to_os_string(&portable), Ok(input));
+    }
+
+    #[test]
+    fn test_portable_string_unknown_tag() {
+        for input in ["", "hello", "x:abc", "U:abc"] {
+            assert_eq!(
+                portable_str_to_os_string(input),
+                Err(PortableStringError::UnknownTag),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_portable_string_unix() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let input = OsString::from_vec(b"\xff\xfe".to_vec());
+        let portable = os_string_to_portable_string(&input);
+        assert_eq!(portable, "b:777A====");
+        assert_eq!(portable_str_to_os_string(&portable), Ok(input));
+
+        assert_eq!(
+            portable_str_to_os_string("w:MEAA===="),
+            Err(PortableStringError::WrongPlatform)
+        );
+        assert_eq!(
+            portable_str_to_os_string("b:777A"),
+            Err(PortableStringError::Base32(Base32Error::InvalidLength(4)))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_portable_string_windows() {
+        let input = u16_vec_to_os_string_windows(vec![0x61, 0xd800]);
+        let portable = os_string_to_portable_string(&input);
+        assert!(portable.starts_with("w:"), "{}", portable);
+        assert_eq!(portable_str_to_os_string(&portable), Ok(input));
+
+        assert_eq!(
+            portable_str_to_os_string("b:777A===="),
+            Err(PortableStringError::WrongPlatform)
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_u16_vec_round_trip_windows() {
+        // An unpaired lead surrogate between two ASCII characters.
+        let wide = vec![0x61, 0xD800, 0x62];
+        let os_string = u16_vec_to_os_string_windows(wide.clone());
+        assert_eq!(os_string.to_str(), None);
+        assert_eq!(os_string_to_u16_vec_windows(&os_string), wide);
+    }
+}
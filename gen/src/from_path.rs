@@ -1,8 +1,14 @@
+use std::borrow::Cow;
+#[cfg(unix)]
 use std::ffi::FromBytesWithNulError;
+#[cfg(unix)]
 use std::ffi::NulError;
+#[cfg(unix)]
 use std::ffi::{CStr, CString};
 use std::ffi::{OsStr, OsString};
+#[cfg(unix)]
 use std::os::unix::ffi::OsStrExt;
+use std::path::Component;
 use std::path::{Path, PathBuf};
 
 // Returns None if the input is not valid UTF-8.
@@ -16,11 +22,13 @@ pub fn path_to_string(input: &Path) -> Option<String> {
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn path_to_u8_slice_unix(input: &Path) -> &[u8] {
     input.as_os_str().as_bytes()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn path_to_u8_vec_unix(input: &Path) -> Vec<u8> {
     input.as_os_str().as_bytes().to_vec()
 }
@@ -43,6 +51,7 @@ pub fn path_to_os_string(input: &Path) -> OsString {
 // terminated or contains any interior nul bytes. If your input is not nul-
 // terminated then a conversion without allocation is not possible, convert
 // to a CString instead.
+#[cfg(unix)]
 pub fn path_to_c_str_unix(
     input: &Path,
 ) -> Result<&CStr, FromBytesWithNulError> {
@@ -52,6 +61,621 @@ pub fn path_to_c_str_unix(
 // This conversion is only allowed on Unix.
 //
 // A NulError will be returned if the input contains any nul bytes.
+#[cfg(unix)]
 pub fn path_to_c_string_unix(input: &Path) -> Result<CString, NulError> {
     CString::new(input.as_os_str().as_bytes())
 }
+
+// The first element is the input itself. For an absolute path the last
+// element is the root (`/` on Unix, the prefix root such as `C:\` on
+// Windows); for a relative path it is the empty path.
+pub fn path_to_ancestor_path_bufs(input: &Path) -> Vec<PathBuf> {
+    input.ancestors().map(Path::to_path_buf).collect()
+}
+
+// Returns true if the input is valid UTF-8. This does not allocate, so
+// it can be used to choose between `path_to_str` and a lossy conversion.
+pub fn path_is_utf8(input: &Path) -> bool {
+    input.to_str().is_some()
+}
+
+// Converts the input to a string with `/` as the separator, suitable
+// for storing in a portable config file. On Unix this is the same as
+// `to_string_lossy`; on Windows each `\` is replaced with `/`. Invalid
+// UTF-8 sequences will be replaced with "�".
+//
+// Round-tripping through `slash_str_to_path_buf` on the same platform
+// gives back the original path as long as it is valid UTF-8. When the
+// string is read on a different platform, the path is only preserved
+// if no component contains a literal `\`, which is allowed in Unix
+// file names.
+pub fn path_to_slash_string_lossy(input: &Path) -> Cow<'_, str> {
+    let s = input.to_string_lossy();
+    if cfg!(windows) {
+        Cow::Owned(s.replace('\\', "/"))
+    } else {
+        s
+    }
+}
+
+// The inverse of `path_to_slash_string_lossy`. On Unix the input is
+// used as-is; on Windows each `/` is replaced with `\`. Nothing other
+// than separators is changed.
+pub fn slash_str_to_path_buf(input: &str) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(input.replace('/', "\\"))
+    } else {
+        PathBuf::from(input)
+    }
+}
+
+// Invalid UTF-8 sequences will be replaced with "�", then the glob
+// metacharacters `*`, `?`, `[` and `]` are escaped with a backslash so
+// that the result only matches the input path.
+//
+// On Unix a literal `\` in a file name is escaped as well. On Windows
+// `\` is a separator, so it is replaced with `/` (which glob matchers
+// accept on Windows) to avoid it being read as an escape.
+pub fn path_to_glob_escaped_string_lossy(input: &Path) -> String {
+    let mut out = String::new();
+    for c in input.to_string_lossy().chars() {
+        match c {
+            '\\' if cfg!(windows) => out.push('/'),
+            '*' | '?' | '[' | ']' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// This conversion is only allowed on Windows.
+//
+// Adds the `\\?\` verbatim prefix, which lifts the `MAX_PATH` length
+// limit but turns off normalization, so only absolute paths with no
+// `..` components are converted. Any other input, including a path
+// that is already verbatim, is returned unchanged. A UNC path such as
+// `\\server\share` becomes `\\?\UNC\server\share`.
+#[cfg(windows)]
+pub fn path_to_verbatim_path_buf_windows(input: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    let mut components = input.components();
+    let mut out = OsString::from(r"\\?\");
+    match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(disk) => {
+                out.push(format!("{}:", char::from(disk)));
+            }
+            Prefix::UNC(server, share) => {
+                out.push(r"UNC\");
+                out.push(server);
+                out.push(r"\");
+                out.push(share);
+            }
+            _ => return input.to_path_buf(),
+        },
+        _ => return input.to_path_buf(),
+    }
+    if components.next() != Some(Component::RootDir) {
+        return input.to_path_buf();
+    }
+    out.push(r"\");
+
+    let mut first = true;
+    for component in components {
+        match component {
+            Component::Normal(name) => {
+                if !first {
+                    out.push(r"\");
+                }
+                out.push(name);
+                first = false;
+            }
+            _ => return input.to_path_buf(),
+        }
+    }
+
+    PathBuf::from(out)
+}
+
+// This conversion is only allowed on Windows.
+//
+// Removes a `\\?\` verbatim prefix from a disk or UNC path, so
+// `\\?\C:\dir` becomes `C:\dir` and `\\?\UNC\server\share` becomes
+// `\\server\share`. Any other input is returned unchanged. Note that
+// the result may be too long for APIs limited to `MAX_PATH`.
+#[cfg(windows)]
+pub fn path_strip_verbatim_windows(input: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    let mut components = input.components();
+    let mut out = OsString::new();
+    match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::VerbatimDisk(disk) => {
+                out.push(format!("{}:", char::from(disk)));
+            }
+            Prefix::VerbatimUNC(server, share) => {
+                out.push(r"\\");
+                out.push(server);
+                out.push(r"\");
+                out.push(share);
+            }
+            _ => return input.to_path_buf(),
+        },
+        _ => return input.to_path_buf(),
+    }
+    out.push(components.as_path());
+
+    PathBuf::from(out)
+}
+
+// Returns None if the input has no parent, such as a root or the empty
+// path. For a single relative component like `file.txt` the parent is
+// the empty path. This does not touch the filesystem.
+pub fn path_to_parent_path_buf(input: &Path) -> Option<PathBuf> {
+    input.parent().map(Path::to_path_buf)
+}
+
+// Returns None if the input has no file name. Only the last extension
+// is removed, so the stem of `archive.tar.gz` is `archive.tar`. Invalid
+// UTF-8 sequences will be replaced with "�".
+pub fn path_to_stem_string_lossy(input: &Path) -> Option<Cow<'_, str>> {
+    input.file_stem().map(|stem| stem.to_string_lossy())
+}
+
+// This conversion is only allowed on Windows.
+//
+// Unlike `to_string_lossy`, this never loses information. Windows
+// paths are UTF-16 and may contain unpaired surrogates; each one is
+// written as `\u{dxxx}` with the code unit in lowercase hex. All other
+// characters are copied as-is.
+//
+// So that the escapes can be told apart from text that happens to look
+// like one, a run of backslashes is doubled if it is followed by `u{`
+// in the input or by an escape. To reverse the conversion, for a run
+// of `n` backslashes followed by `u{`: if `n` is even it stands for
+// `n / 2` backslashes and a literal `u{`, otherwise for `(n - 1) / 2`
+// backslashes and an escape. Any other backslashes are literal.
+#[cfg(windows)]
+pub fn path_to_string_windows(input: &Path) -> String {
+    use std::os::windows::ffi::OsStrExt;
+
+    let decoded = char::decode_utf16(input.as_os_str().encode_wide())
+        .map(|r| r.map_err(|e| e.unpaired_surrogate()))
+        .collect::<Vec<_>>();
+
+    let mut out = String::with_capacity(decoded.len());
+    let mut backslashes = 0;
+    for (i, item) in decoded.iter().enumerate() {
+        let double = match item {
+            Ok('\\') => {
+                backslashes += 1;
+                continue;
+            }
+            Ok('u') => decoded.get(i + 1) == Some(&Ok('{')),
+            Ok(_) => false,
+            Err(_) => true,
+        };
+
+        let n = if double { backslashes * 2 } else { backslashes };
+        out.push_str(&"\\".repeat(n));
+        backslashes = 0;
+
+        match item {
+            Ok(c) => out.push(*c),
+            Err(unit) => out.push_str(&format!("\\u{{{:x}}}", unit)),
+        }
+    }
+    out.push_str(&"\\".repeat(backslashes));
+
+    out
+}
+
+// Invalid UTF-8 sequences will be replaced with "�", then the result is
+// case folded so that it can be used as a key in a map of paths on a
+// case-insensitive filesystem. Two paths that differ only in case give
+// equal keys.
+//
+// With the `unicode` feature this applies full Unicode case folding,
+// so for example "STRASSE" and "straße" match. Without it only ASCII
+// letters are folded.
+#[cfg(feature = "unicode")]
+pub fn path_to_case_fold_key_lossy(input: &Path) -> String {
+    caseless::default_case_fold_str(&input.to_string_lossy())
+}
+
+// See the `unicode` version above.
+#[cfg(not(feature = "unicode"))]
+pub fn path_to_case_fold_key_lossy(input: &Path) -> String {
+    input.to_string_lossy().to_ascii_lowercase()
+}
+
+// This conversion is only allowed on Windows.
+//
+// Returns the path encoded as WTF-8, which is UTF-8 extended so that
+// unpaired surrogates in the UTF-16 form of the path are encoded as
+// three-byte sequences, in the same way as any other code point in
+// that range. This never loses information and can be reversed with
+// `wtf8_u8_slice_to_path_buf_windows`. If the path is valid Unicode
+// the output is ordinary UTF-8; otherwise it is not valid UTF-8.
+#[cfg(windows)]
+pub fn path_to_u8_vec_windows(input: &Path) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+
+    let mut out = Vec::with_capacity(input.as_os_str().len());
+    for item in char::decode_utf16(input.as_os_str().encode_wide()) {
+        match item {
+            Ok(c) => {
+                let mut buf = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            Err(err) => {
+                let unit = err.unpaired_surrogate();
+                out.push(0xe0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    out
+}
+
+// This conversion is only allowed on Windows.
+//
+// The inverse of `path_to_u8_vec_windows`. Returns None if the input is
+// not valid WTF-8; see `wtf8_u8_slice_to_u16_vec`.
+#[cfg(windows)]
+pub fn wtf8_u8_slice_to_path_buf_windows(input: &[u8]) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    let units = crate::from_u8_slice::wtf8_u8_slice_to_u16_vec(input)?;
+    Some(PathBuf::from(OsString::from_wide(&units)))
+}
+
+// Expresses `input` relative to `base`, adding `..` components as
+// needed. This is purely lexical; the filesystem is not accessed and
+// symlinks are not resolved. If the paths are identical the result is
+// empty, and if `base` is a prefix of `input` the result is the rest
+// of `input`.
+//
+// Returns None if no relative path exists, for example if one path is
+// absolute and the other is not, the paths are on different Windows
+// drives, or `base` has a `..` component that isn't shared with
+// `input`.
+pub fn path_relative_to(input: &Path, base: &Path) -> Option<PathBuf> {
+    let mut input_iter = input
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .peekable();
+    let mut base_iter = base
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .peekable();
+
+    while let (Some(a), Some(b)) = (input_iter.peek(), base_iter.peek()) {
+        if a != b {
+            break;
+        }
+        input_iter.next();
+        base_iter.next();
+    }
+
+    let mut out = PathBuf::new();
+    for c in base_iter {
+        match c {
+            Component::Normal(_) => out.push(".."),
+            _ => return None,
+        }
+    }
+    for c in input_iter {
+        match c {
+            Component::Normal(_) | Component::ParentDir => out.push(c),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+// Returns one string per component of the input, for comparing paths
+// component by component. `Normal` components and Windows prefixes are
+// converted lossily, with invalid sequences replaced by "�". The other
+// components are mapped to fixed strings that don't depend on the
+// platform: the root is always "/" (even on Windows, where it is
+// written `\`), and the current and parent directories are "." and
+// "..". No filesystem access is done, so `..` is not resolved.
+pub fn path_to_component_keys_lossy(input: &Path) -> Vec<String> {
+    input
+        .components()
+        .map(|c| match c {
+            Component::RootDir => "/".to_string(),
+            Component::CurDir => ".".to_string(),
+            Component::ParentDir => "..".to_string(),
+            Component::Prefix(p) => {
+                p.as_os_str().to_string_lossy().into_owned()
+            }
+            Component::Normal(s) => s.to_string_lossy().into_owned(),
+        })
+        .collect()
+}
+
+// This never fails. Returns the same text as printing
+// `input.display()`. Use this for display only, not for reconstructing
+// the path; invalid sequences are replaced with "�", and the exact
+// output is not guaranteed to match `Path::to_string_lossy`.
+pub fn path_to_display_string(input: &Path) -> String {
+    input.display().to_string()
+}
+
+// Returns the extension of the file name, lowercased so it can be
+// matched against a list of known types. Only the last extension is
+// returned, so "x.TAR.GZ" gives "gz". Returns None if there is no
+// extension. Invalid sequences are replaced with "�".
+//
+// With the `unicode` feature this applies full Unicode case folding.
+// Without it only ASCII letters are lowercased.
+#[cfg(feature = "unicode")]
+pub fn path_to_extension_lower_string(input: &Path) -> Option<String> {
+    input
+        .extension()
+        .map(|ext| caseless::default_case_fold_str(&ext.to_string_lossy()))
+}
+
+// See the `unicode` version above.
+#[cfg(not(feature = "unicode"))]
+pub fn path_to_extension_lower_string(input: &Path) -> Option<String> {
+    input
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+// Returns every extension of the file name, in order. Everything after
+// the first `.` is split on `.`, so "x.tar.gz" gives ["tar", "gz"]. A
+// leading `.` is part of the name rather than an extension separator,
+// so ".bashrc" has no extensions, matching `Path::extension`. Invalid
+// sequences are replaced with "�"; case is not changed.
+pub fn path_to_extensions_lossy(input: &Path) -> Vec<String> {
+    let name = match input.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return Vec::new(),
+    };
+    let name = name.strip_prefix('.').unwrap_or(&name);
+    name.split('.').skip(1).map(str::to_string).collect()
+}
+
+// This never fails. Returns a 64-bit FNV-1a hash of the path, for
+// deduplicating paths without storing them. Unlike `DefaultHasher`,
+// which is randomly seeded, the hash is fixed: equal paths give equal
+// values in every run of every program. Different paths can collide,
+// so check for equality as well if that matters.
+//
+// The raw bytes are hashed on Unix and the UTF-16 code units on
+// Windows, so no information is lost, but the same path gives
+// different values on the two platforms. Paths are compared as written;
+// "a/b" and "a//b" give different values.
+pub fn path_to_hash_u64(input: &Path) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = |acc: u64, byte: u8| (acc ^ u64::from(byte)).wrapping_mul(PRIME);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        input
+            .as_os_str()
+            .as_bytes()
+            .iter()
+            .copied()
+            .fold(OFFSET_BASIS, hash)
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        input
+            .as_os_str()
+            .encode_wide()
+            .flat_map(u16::to_le_bytes)
+            .fold(OFFSET_BASIS, hash)
+    }
+}
+
+// Joins `input` onto `cwd` (unless `input` is already absolute), then
+// removes `.` components and resolves `..` lexically, and returns the
+// result with `/` separators as in `path_to_slash_string_lossy`. This
+// never accesses the filesystem, so symlinks are not followed and the
+// result doesn't depend on the process's working directory. A `..`
+// at the root is dropped, as in POSIX path resolution.
+//
+// `cwd` should itself be absolute; if it is not, the result is
+// relative and may start with `..`.
+pub fn path_to_absolute_string(input: &Path, cwd: &Path) -> String {
+    let joined = cwd.join(input);
+    let mut out: Vec<Component> = Vec::new();
+    for c in joined.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => match out.last() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => out.push(c),
+            },
+            _ => out.push(c),
+        }
+    }
+    let path = out.iter().collect::<PathBuf>();
+    path_to_slash_string_lossy(&path).into_owned()
+}
+
+// Extensions of common file types and their MIME types, for
+// `path_to_mime_type_str`. Extensions are lowercase.
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("md", "text/markdown"),
+    ("mjs", "text/javascript"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("tar", "application/x-tar"),
+    ("txt", "text/plain"),
+    ("wasm", "application/wasm"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xml", "application/xml"),
+    ("zip", "application/zip"),
+];
+
+// This never fails. Guesses the MIME type of a file from its
+// extension, ignoring ASCII case, so "foo.JSON" gives
+// "application/json". Only the last extension is used, and the file's
+// contents are not read. Unknown or missing extensions give
+// "application/octet-stream".
+pub fn path_to_mime_type_str(input: &Path) -> &'static str {
+    input
+        .extension()
+        .and_then(|ext| {
+            MIME_TYPES
+                .iter()
+                .find(|(known, _)| ext.eq_ignore_ascii_case(known))
+        })
+        .map(|(_, mime)| *mime)
+        .unwrap_or("application/octet-stream")
+}
+
+// Returns the names of the input's `Normal` components, borrowed from
+// the input. The root, any Windows prefix, and `.` and `..` components
+// are skipped without being resolved, so "/a/b/../c" gives
+// ["a", "b", "c"]; use `path_to_absolute_string` first if `..` should
+// be resolved.
+pub fn path_to_normal_components(input: &Path) -> Vec<&OsStr> {
+    input
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+// Returns the number of components that `path_to_normal_components`
+// would return, without allocating. "/" has a depth of 0 and "/a/b"
+// has a depth of 2.
+pub fn path_depth(input: &Path) -> usize {
+    input
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_is_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        assert!(path_is_utf8(Path::new("a/b.txt")));
+        assert!(!path_is_utf8(Path::new(OsStr::from_bytes(&[0xff]))));
+    }
+
+    #[test]
+    fn test_path_relative_to() {
+        let cases = &[
+            ("a/b/c", "a/b", Some("c")),
+            ("a/b", "a/b", Some("")),
+            ("a/b", "a/b/c/d", Some("../..")),
+            ("a/x/y", "a/b", Some("../x/y")),
+            ("./a/b", "a/.", Some("b")),
+            ("../a", "../b", Some("../a")),
+            // `..` in the input is kept, not resolved.
+            ("a/../b", "a", Some("../b")),
+            ("/a/b", "/c", Some("../a/b")),
+            ("/", "/a", Some("..")),
+            // One path is rooted and the other is not.
+            ("/a", "a", None),
+            ("a", "/a", None),
+            // The base has a `..` that the input doesn't share.
+            ("a", "../b", None),
+        ];
+        for (input, base, expected) in cases {
+            assert_eq!(
+                path_relative_to(Path::new(input), Path::new(base)),
+                expected.map(PathBuf::from),
+                "{:?} relative to {:?}",
+                input,
+                base
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_to_absolute_string() {
+        let cases = &[
+            ("a/b", "/home/u", "/home/u/a/b"),
+            ("./a/./b/", "/home/u", "/home/u/a/b"),
+            ("../a", "/home/u", "/home/a"),
+            ("a/../../..", "/home/u", "/"),
+            // A `..` at the root is dropped.
+            ("../../../../a", "/home/u", "/a"),
+            ("..", "/", "/"),
+            // An absolute input ignores `cwd`.
+            ("/etc/../var", "/home/u", "/var"),
+            ("", "/home/u", "/home/u"),
+            // A relative `cwd` gives a relative result.
+            ("../../a", "w", "../a"),
+        ];
+        for (input, cwd, expected) in cases {
+            assert_eq!(
+                path_to_absolute_string(Path::new(input), Path::new(cwd)),
+                *expected,
+                "{:?} in {:?}",
+                input,
+                cwd
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_wtf8_u8_slice_to_path_buf_windows() {
+        use std::os::windows::ffi::OsStrExt;
+
+        // An unpaired surrogate survives the round trip.
+        let path = wtf8_u8_slice_to_path_buf_windows(b"a\xed\xa0\x80").unwrap();
+        let units = path.as_os_str().encode_wide().collect::<Vec<_>>();
+        assert_eq!(units, [0x61, 0xd800]);
+        assert_eq!(path_to_u8_vec_windows(&path), b"a\xed\xa0\x80");
+
+        assert_eq!(
+            wtf8_u8_slice_to_path_buf_windows(b"\xed\xa0\x80\xed\xb0\x80"),
+            None
+        );
+    }
+}
@@ -1,7 +1,11 @@
+#[cfg(unix)]
 use std::ffi::FromBytesWithNulError;
+#[cfg(unix)]
 use std::ffi::NulError;
+#[cfg(unix)]
 use std::ffi::{CStr, CString};
 use std::ffi::{OsStr, OsString};
+#[cfg(unix)]
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::path::{Path, PathBuf};
 
@@ -12,15 +16,17 @@ pub fn path_buf_to_str(input: &PathBuf) -> Option<&str> {
 
 // Returns None if the input is not valid UTF-8.
 pub fn path_buf_to_string(input: PathBuf) -> Option<String> {
-    input.as_path().to_str().map(|s| s.to_string())
+    input.into_os_string().into_string().ok()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn path_buf_to_u8_slice_unix(input: &PathBuf) -> &[u8] {
     input.as_os_str().as_bytes()
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn path_buf_to_u8_vec_unix(input: PathBuf) -> Vec<u8> {
     input.into_os_string().into_vec()
 }
@@ -43,6 +49,7 @@ pub fn path_buf_to_os_string(input: PathBuf) -> OsString {
 // terminated or contains any interior nul bytes. If your input is not nul-
 // terminated then a conversion without allocation is not possible, convert
 // to a CString instead.
+#[cfg(unix)]
 pub fn path_buf_to_c_str_unix(
     input: &PathBuf,
 ) -> Result<&CStr, FromBytesWithNulError> {
@@ -52,6 +59,31 @@ pub fn path_buf_to_c_str_unix(
 // This conversion is only allowed on Unix.
 //
 // A NulError will be returned if the input contains any nul bytes.
+#[cfg(unix)]
 pub fn path_buf_to_c_string_unix(input: PathBuf) -> Result<CString, NulError> {
     CString::new(input.into_os_string().into_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_buf_to_string_reuses_allocation() {
+        let input = PathBuf::from(String::with_capacity(16) + "a/b.txt");
+        let ptr = input.to_str().unwrap().as_ptr();
+        let output = path_buf_to_string(input).unwrap();
+        assert_eq!(output, "a/b.txt");
+        assert_eq!(output.as_ptr(), ptr);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_buf_to_u8_vec_unix_reuses_allocation() {
+        let input = PathBuf::from(OsString::from_vec(b"a/\xff".to_vec()));
+        let ptr = input.as_os_str().as_bytes().as_ptr();
+        let output = path_buf_to_u8_vec_unix(input);
+        assert_eq!(output, b"a/\xff");
+        assert_eq!(output.as_ptr(), ptr);
+    }
+}
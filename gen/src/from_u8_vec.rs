@@ -1,8 +1,11 @@
 use std::ffi::FromBytesWithNulError;
 use std::ffi::NulError;
 use std::ffi::{CStr, CString};
+#[cfg(unix)]
 use std::ffi::{OsStr, OsString};
+#[cfg(unix)]
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
+#[cfg(unix)]
 use std::path::{Path, PathBuf};
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
@@ -11,6 +14,9 @@ pub fn u8_vec_to_str(input: &Vec<u8>) -> Result<&str, Utf8Error> {
     std::str::from_utf8(input)
 }
 
+// On success this reuses the input's allocation: the result has the same
+// pointer and capacity as the input, and no bytes are copied. On failure
+// the input can be recovered with `FromUtf8Error::into_bytes`.
 pub fn u8_vec_to_string(input: Vec<u8>) -> Result<String, FromUtf8Error> {
     String::from_utf8(input)
 }
@@ -20,21 +26,25 @@ pub fn u8_vec_to_u8_slice(input: &Vec<u8>) -> &[u8] {
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn u8_vec_to_path_unix(input: &Vec<u8>) -> &Path {
     Path::new(OsStr::from_bytes(input))
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn u8_vec_to_path_buf_unix(input: Vec<u8>) -> PathBuf {
     PathBuf::from(OsString::from_vec(input))
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn u8_vec_to_os_str_unix(input: &Vec<u8>) -> &OsStr {
     OsStr::from_bytes(input)
 }
 
 // This conversion is only allowed on Unix.
+#[cfg(unix)]
 pub fn u8_vec_to_os_string_unix(input: Vec<u8>) -> OsString {
     OsString::from_vec(input)
 }
@@ -53,3 +63,100 @@ pub fn u8_vec_to_c_str(
 pub fn u8_vec_to_c_string(input: Vec<u8>) -> Result<CString, NulError> {
     CString::new(input)
 }
+
+// This never fails. Returns the longest valid UTF-8 prefix of the
+// input as a `String`, reusing the input's allocation, and the rest of
+// the bytes starting at the first invalid or incomplete sequence. If
+// the input is read incrementally, the tail can be prepended to the
+// next chunk.
+pub fn u8_vec_to_string_salvage(input: Vec<u8>) -> (String, Vec<u8>) {
+    match String::from_utf8(input) {
+        Ok(s) => (s, Vec::new()),
+        Err(err) => {
+            let valid_up_to = err.utf8_error().valid_up_to();
+            let mut prefix = err.into_bytes();
+            let tail = prefix.split_off(valid_up_to);
+            // `prefix` holds exactly the bytes before `valid_up_to`, so
+            // this can't fail.
+            let prefix = String::from_utf8(prefix)
+                .expect("prefix is valid UTF-8 up to valid_up_to");
+            (prefix, tail)
+        }
+    }
+}
+
+// This never fails. The input is truncated at the first nul byte, if
+// any, so the result holds the text a C function would see. This
+// reuses the input's allocation, growing it by one byte for the nul
+// terminator if needed.
+pub fn u8_vec_to_c_string_truncating(mut input: Vec<u8>) -> CString {
+    if let Some(i) = input.iter().position(|b| *b == 0) {
+        input.truncate(i);
+    }
+    CString::new(input).expect("input has no nul bytes after truncating")
+}
+
+// This never fails. If the input is valid UTF-8 its allocation is
+// reused and nothing is copied. Otherwise invalid sequences are
+// replaced with "�" in a new allocation.
+pub fn u8_vec_to_string_or_lossy(input: Vec<u8>) -> String {
+    String::from_utf8(input).unwrap_or_else(|err| {
+        String::from_utf8_lossy(err.as_bytes()).into_owned()
+    })
+}
+
+// A `Box<str>` is a pointer and a length with no capacity, which saves
+// a word for strings that are stored and never changed. The input's
+// allocation is reused if its capacity equals its length; otherwise
+// `into_boxed_str` shrinks it, which may reallocate and copy.
+//
+// A FromUtf8Error will be returned if the input is not valid UTF-8.
+// The input can be recovered with `FromUtf8Error::into_bytes`.
+pub fn u8_vec_to_box_str(input: Vec<u8>) -> Result<Box<str>, FromUtf8Error> {
+    String::from_utf8(input).map(String::into_boxed_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u8_vec_to_string_or_lossy_reuses_allocation() {
+        let mut input = Vec::with_capacity(16);
+        input.extend_from_slice(b"abc");
+        let (ptr, capacity) = (input.as_ptr(), input.capacity());
+        let output = u8_vec_to_string_or_lossy(input);
+        assert_eq!(output, "abc");
+        assert_eq!(output.as_ptr(), ptr);
+        assert_eq!(output.capacity(), capacity);
+
+        assert_eq!(u8_vec_to_string_or_lossy(b"a\xffb".to_vec()), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn test_u8_vec_to_string_reuses_allocation() {
+        let mut input = Vec::with_capacity(16);
+        input.extend_from_slice(b"abc");
+        let (ptr, capacity) = (input.as_ptr(), input.capacity());
+        let output = u8_vec_to_string(input).unwrap();
+        assert_eq!(output.as_ptr(), ptr);
+        assert_eq!(output.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_u8_vec_to_string_salvage() {
+        assert_eq!(
+            u8_vec_to_string_salvage(b"abc".to_vec()),
+            ("abc".to_string(), Vec::new())
+        );
+        // An incomplete sequence is returned for the next chunk.
+        assert_eq!(
+            u8_vec_to_string_salvage(b"ab\xe2\x82".to_vec()),
+            ("ab".to_string(), b"\xe2\x82".to_vec())
+        );
+        assert_eq!(
+            u8_vec_to_string_salvage(b"a\xffb".to_vec()),
+            ("a".to_string(), b"\xffb".to_vec())
+        );
+    }
+}
@@ -0,0 +1,42 @@
+// A byte slice that is checked for valid UTF-8 only when it is first
+// read as a string. Creating one never validates or allocates, so it
+// is useful when most of the strings on a hot path are never looked
+// at. The result of the check is cached, so later reads are free.
+
+use std::cell::OnceCell;
+use std::str::Utf8Error;
+
+#[derive(Clone, Debug)]
+pub struct LazyStr<'a> {
+    bytes: &'a [u8],
+    checked: OnceCell<Result<&'a str, Utf8Error>>,
+}
+
+impl<'a> LazyStr<'a> {
+    // Validates the bytes on the first call; later calls return the
+    // cached result. A Utf8Error will be returned if the bytes are not
+    // valid UTF-8.
+    pub fn as_str(&self) -> Result<&'a str, Utf8Error> {
+        *self.checked.get_or_init(|| std::str::from_utf8(self.bytes))
+    }
+
+    // Returns the underlying bytes without validating them.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+// This never fails, validates, or allocates. UTF-8 errors are reported
+// by `LazyStr::as_str`.
+pub fn u8_slice_to_lazy_str(input: &[u8]) -> LazyStr<'_> {
+    LazyStr {
+        bytes: input,
+        checked: OnceCell::new(),
+    }
+}
+
+// A Utf8Error will be returned if the input is not valid UTF-8. If the
+// input has already been read this does not validate it again.
+pub fn lazy_str_to_str<'a>(input: &LazyStr<'a>) -> Result<&'a str, Utf8Error> {
+    input.as_str()
+}
@@ -5,13 +5,24 @@
 // these types.
 #![allow(clippy::ptr_arg)]
 
+pub mod c_string_arena;
+pub mod encoding;
+pub mod env;
+pub mod from_box_c_str;
 pub mod from_c_str;
 pub mod from_c_string;
 pub mod from_os_str;
 pub mod from_os_string;
+pub mod from_os_string_vec;
 pub mod from_path;
 pub mod from_path_buf;
 pub mod from_str;
 pub mod from_string;
 pub mod from_u8_slice;
 pub mod from_u8_vec;
+pub mod lazy_str;
+pub mod non_empty_str;
+pub mod redact;
+pub mod registry;
+pub mod sanitize;
+pub mod traced;
@@ -0,0 +1,22 @@
+// Conversions that mask sensitive text, such as passwords and API
+// keys. Unlike the `sanitize` module, the goal is hiding the content
+// rather than making it valid in some context.
+
+// This never fails. Replaces every character except the last
+// `keep_last` with `*`, so a secret can be logged without revealing
+// it. Characters are counted as `char`s, not bytes, so a multi-byte
+// character is never split; each masked character becomes one `*`.
+// If `keep_last` is at least the number of characters the input is
+// returned unchanged.
+pub fn str_to_redacted_string(input: &str, keep_last: usize) -> String {
+    let masked = input.chars().count().saturating_sub(keep_last);
+    let kept = input
+        .char_indices()
+        .nth(masked)
+        .map(|(i, _)| &input[i..])
+        .unwrap_or("");
+    let mut out = String::with_capacity(masked + kept.len());
+    out.extend(std::iter::repeat_n('*', masked));
+    out.push_str(kept);
+    out
+}
@@ -0,0 +1,295 @@
+use std::ffi::FromBytesWithNulError;
+use std::ffi::IntoStringError;
+use std::ffi::NulError;
+use std::ffi::{CStr, CString};
+#[cfg(unix)]
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+
+// Errors from a conversion, annotated with which conversion failed.
+// When a conversion deep inside a chain of calls fails, the underlying
+// error often doesn't say enough to find it; a `Traced` error names the
+// types and the function as well.
+//
+// The `_traced` functions below are generated, one for each fallible
+// generated conversion in the `from_*` modules. The hand-written
+// conversions in those modules don't have `_traced` variants.
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Traced<E> {
+    // Short name of the input type, such as "u8_slice".
+    pub from: &'static str,
+    // Short name of the output type, such as "str".
+    pub to: &'static str,
+    // Name of the conversion function that failed.
+    pub function: &'static str,
+    // The underlying error.
+    pub source: E,
+}
+
+impl<E> Traced<E> {
+    pub fn new(
+        from: &'static str,
+        to: &'static str,
+        function: &'static str,
+        source: E,
+    ) -> Self {
+        Traced {
+            from,
+            to,
+            function,
+            source,
+        }
+    }
+}
+
+// Formats as "<from> -> <to>: <source>", for example
+// "u8_slice -> str: invalid utf-8 sequence of 1 bytes from index 3".
+impl<E: fmt::Display> fmt::Display for Traced<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} -> {}: {}", self.from, self.to, self.source)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Traced<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// Like `from_str::str_to_c_str`, but the error names the
+// conversion.
+pub fn str_to_c_str_traced(
+    input: &str,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_str::str_to_c_str(input)
+        .map_err(|err| Traced::new("str", "c_str", "str_to_c_str", err))
+}
+
+// Like `from_str::str_to_c_string`, but the error names the
+// conversion.
+pub fn str_to_c_string_traced(
+    input: &str,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_str::str_to_c_string(input)
+        .map_err(|err| Traced::new("str", "c_string", "str_to_c_string", err))
+}
+
+// Like `from_string::string_to_c_str`, but the error names the
+// conversion.
+pub fn string_to_c_str_traced(
+    input: &String,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_string::string_to_c_str(input)
+        .map_err(|err| Traced::new("string", "c_str", "string_to_c_str", err))
+}
+
+// Like `from_string::string_to_c_string`, but the error names the
+// conversion.
+pub fn string_to_c_string_traced(
+    input: String,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_string::string_to_c_string(input).map_err(|err| {
+        Traced::new("string", "c_string", "string_to_c_string", err)
+    })
+}
+
+// Like `from_u8_slice::u8_slice_to_str`, but the error names the
+// conversion.
+pub fn u8_slice_to_str_traced(input: &[u8]) -> Result<&str, Traced<Utf8Error>> {
+    crate::from_u8_slice::u8_slice_to_str(input)
+        .map_err(|err| Traced::new("u8_slice", "str", "u8_slice_to_str", err))
+}
+
+// Like `from_u8_slice::u8_slice_to_string`, but the error names the
+// conversion.
+pub fn u8_slice_to_string_traced(
+    input: &[u8],
+) -> Result<String, Traced<FromUtf8Error>> {
+    crate::from_u8_slice::u8_slice_to_string(input).map_err(|err| {
+        Traced::new("u8_slice", "string", "u8_slice_to_string", err)
+    })
+}
+
+// Like `from_u8_slice::u8_slice_to_c_str`, but the error names the
+// conversion.
+pub fn u8_slice_to_c_str_traced(
+    input: &[u8],
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_u8_slice::u8_slice_to_c_str(input).map_err(|err| {
+        Traced::new("u8_slice", "c_str", "u8_slice_to_c_str", err)
+    })
+}
+
+// Like `from_u8_slice::u8_slice_to_c_string`, but the error names the
+// conversion.
+pub fn u8_slice_to_c_string_traced(
+    input: &[u8],
+) -> Result<CString, Traced<NulError>> {
+    crate::from_u8_slice::u8_slice_to_c_string(input).map_err(|err| {
+        Traced::new("u8_slice", "c_string", "u8_slice_to_c_string", err)
+    })
+}
+
+// Like `from_u8_vec::u8_vec_to_str`, but the error names the
+// conversion.
+pub fn u8_vec_to_str_traced(
+    input: &Vec<u8>,
+) -> Result<&str, Traced<Utf8Error>> {
+    crate::from_u8_vec::u8_vec_to_str(input)
+        .map_err(|err| Traced::new("u8_vec", "str", "u8_vec_to_str", err))
+}
+
+// Like `from_u8_vec::u8_vec_to_string`, but the error names the
+// conversion.
+pub fn u8_vec_to_string_traced(
+    input: Vec<u8>,
+) -> Result<String, Traced<FromUtf8Error>> {
+    crate::from_u8_vec::u8_vec_to_string(input)
+        .map_err(|err| Traced::new("u8_vec", "string", "u8_vec_to_string", err))
+}
+
+// Like `from_u8_vec::u8_vec_to_c_str`, but the error names the
+// conversion.
+pub fn u8_vec_to_c_str_traced(
+    input: &Vec<u8>,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_u8_vec::u8_vec_to_c_str(input)
+        .map_err(|err| Traced::new("u8_vec", "c_str", "u8_vec_to_c_str", err))
+}
+
+// Like `from_u8_vec::u8_vec_to_c_string`, but the error names the
+// conversion.
+pub fn u8_vec_to_c_string_traced(
+    input: Vec<u8>,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_u8_vec::u8_vec_to_c_string(input).map_err(|err| {
+        Traced::new("u8_vec", "c_string", "u8_vec_to_c_string", err)
+    })
+}
+
+// Like `from_path::path_to_c_str_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn path_to_c_str_unix_traced(
+    input: &Path,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_path::path_to_c_str_unix(input)
+        .map_err(|err| Traced::new("path", "c_str", "path_to_c_str_unix", err))
+}
+
+// Like `from_path::path_to_c_string_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn path_to_c_string_unix_traced(
+    input: &Path,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_path::path_to_c_string_unix(input).map_err(|err| {
+        Traced::new("path", "c_string", "path_to_c_string_unix", err)
+    })
+}
+
+// Like `from_path_buf::path_buf_to_c_str_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn path_buf_to_c_str_unix_traced(
+    input: &PathBuf,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_path_buf::path_buf_to_c_str_unix(input).map_err(|err| {
+        Traced::new("path_buf", "c_str", "path_buf_to_c_str_unix", err)
+    })
+}
+
+// Like `from_path_buf::path_buf_to_c_string_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn path_buf_to_c_string_unix_traced(
+    input: PathBuf,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_path_buf::path_buf_to_c_string_unix(input).map_err(|err| {
+        Traced::new("path_buf", "c_string", "path_buf_to_c_string_unix", err)
+    })
+}
+
+// Like `from_os_str::os_str_to_c_str_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn os_str_to_c_str_unix_traced(
+    input: &OsStr,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_os_str::os_str_to_c_str_unix(input).map_err(|err| {
+        Traced::new("os_str", "c_str", "os_str_to_c_str_unix", err)
+    })
+}
+
+// Like `from_os_str::os_str_to_c_string_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn os_str_to_c_string_unix_traced(
+    input: &OsStr,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_os_str::os_str_to_c_string_unix(input).map_err(|err| {
+        Traced::new("os_str", "c_string", "os_str_to_c_string_unix", err)
+    })
+}
+
+// Like `from_os_string::os_string_to_c_str_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn os_string_to_c_str_unix_traced(
+    input: &OsString,
+) -> Result<&CStr, Traced<FromBytesWithNulError>> {
+    crate::from_os_string::os_string_to_c_str_unix(input).map_err(|err| {
+        Traced::new("os_string", "c_str", "os_string_to_c_str_unix", err)
+    })
+}
+
+// Like `from_os_string::os_string_to_c_string_unix`, but the error names the
+// conversion.
+#[cfg(unix)]
+pub fn os_string_to_c_string_unix_traced(
+    input: OsString,
+) -> Result<CString, Traced<NulError>> {
+    crate::from_os_string::os_string_to_c_string_unix(input).map_err(|err| {
+        Traced::new("os_string", "c_string", "os_string_to_c_string_unix", err)
+    })
+}
+
+// Like `from_c_str::c_str_to_str`, but the error names the
+// conversion.
+pub fn c_str_to_str_traced(input: &CStr) -> Result<&str, Traced<Utf8Error>> {
+    crate::from_c_str::c_str_to_str(input)
+        .map_err(|err| Traced::new("c_str", "str", "c_str_to_str", err))
+}
+
+// Like `from_c_str::c_str_to_string`, but the error names the
+// conversion.
+pub fn c_str_to_string_traced(
+    input: &CStr,
+) -> Result<String, Traced<Utf8Error>> {
+    crate::from_c_str::c_str_to_string(input)
+        .map_err(|err| Traced::new("c_str", "string", "c_str_to_string", err))
+}
+
+// Like `from_c_string::c_string_to_str`, but the error names the
+// conversion.
+pub fn c_string_to_str_traced(
+    input: &CString,
+) -> Result<&str, Traced<Utf8Error>> {
+    crate::from_c_string::c_string_to_str(input)
+        .map_err(|err| Traced::new("c_string", "str", "c_string_to_str", err))
+}
+
+// Like `from_c_string::c_string_to_string`, but the error names the
+// conversion.
+pub fn c_string_to_string_traced(
+    input: CString,
+) -> Result<String, Traced<IntoStringError>> {
+    crate::from_c_string::c_string_to_string(input).map_err(|err| {
+        Traced::new("c_string", "string", "c_string_to_string", err)
+    })
+}
@@ -48,6 +48,52 @@ enum Type {
     ResultStringOrIntoStringError,
 }
 
+/// Groups of anchor types, used to organize the docs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Category {
+    Text,
+    Bytes,
+    Path,
+    CInterop,
+}
+
+impl Category {
+    fn all() -> &'static [Category] {
+        &[
+            Category::Text,
+            Category::Bytes,
+            Category::Path,
+            Category::CInterop,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Category::Text => "Text",
+            Category::Bytes => "Bytes",
+            Category::Path => "Paths and OS strings",
+            Category::CInterop => "C interop",
+        }
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            Category::Text => "text",
+            Category::Bytes => "bytes",
+            Category::Path => "paths",
+            Category::CInterop => "c_interop",
+        }
+    }
+
+    fn anchors(&self) -> Vec<Type> {
+        Type::anchors()
+            .iter()
+            .copied()
+            .filter(|t| t.category() == *self)
+            .collect()
+    }
+}
+
 impl Type {
     fn anchors() -> &'static [Type] {
         &[
@@ -83,7 +129,7 @@ impl Type {
             Type::OsStringRef => "&OsString",
             Type::CStringRef => "&CString",
 
-            Type::CowStr => "Cow<str>",
+            Type::CowStr => "Cow<'_, str>",
             Type::OptionStr => "Option<&str>",
             Type::OptionString => "Option<String>",
             Type::ResultStrOrUtf8Error => "Result<&str, Utf8Error>",
@@ -123,6 +169,19 @@ impl Type {
         }
     }
 
+    fn category(&self) -> Category {
+        match self {
+            Type::Str | Type::String => Category::Text,
+            Type::U8Slice | Type::U8Vec => Category::Bytes,
+            Type::Path | Type::PathBuf | Type::OsStr | Type::OsString => {
+                Category::Path
+            }
+            Type::CStr | Type::CString => Category::CInterop,
+
+            _ => panic!("no category for {:?}", self),
+        }
+    }
+
     fn uses(&self) -> &'static [&'static str] {
         match self {
             Type::Path => &["std::path::Path"],
@@ -150,6 +209,26 @@ impl Type {
         }
     }
 
+    /// For a `Result` whose error type implements `Error`, get the
+    /// `Ok` and `Err` types.
+    fn result_parts(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Type::ResultStrOrUtf8Error => Some(("&str", "Utf8Error")),
+            Type::ResultStringOrUtf8Error => Some(("String", "Utf8Error")),
+            Type::ResultStringOrFromUtf8Error => {
+                Some(("String", "FromUtf8Error"))
+            }
+            Type::ResultCStrOrFromBytesWithNulError => {
+                Some(("&CStr", "FromBytesWithNulError"))
+            }
+            Type::ResultCStringOrNulError => Some(("CString", "NulError")),
+            Type::ResultStringOrIntoStringError => {
+                Some(("String", "IntoStringError"))
+            }
+            _ => None,
+        }
+    }
+
     /// Optional comment associated with the type when used as a
     /// return value.
     fn return_comment(&self) -> Option<&'static str> {
@@ -182,6 +261,7 @@ struct Conversion {
     format: &'static str,
     os_str_bytes: bool,
     os_string_bytes: bool,
+    comment: Option<&'static str>,
 }
 
 impl Conversion {
@@ -213,6 +293,11 @@ impl Conversion {
         self.os_string_bytes = true;
         self
     }
+
+    fn with_comment(mut self, comment: &'static str) -> Self {
+        self.comment = Some(comment);
+        self
+    }
 }
 
 fn conversion_chains(t1: Type, t2: Type) -> &'static [&'static [Type]] {
@@ -331,9 +416,12 @@ fn conversion_chains(t1: Type, t2: Type) -> &'static [&'static [Type]] {
         (Type::PathBuf, Type::Str) => {
             &[&[Type::PathBufRef, Type::Path, Type::OptionStr]]
         }
-        (Type::PathBuf, Type::String) => {
-            &[&[Type::PathBuf, Type::Path, Type::OptionString]]
-        }
+        (Type::PathBuf, Type::String) => &[&[
+            Type::PathBuf,
+            Type::OsString,
+            Type::ResultStringOrOsString,
+            Type::OptionString,
+        ]],
         (Type::PathBuf, Type::U8Slice) => {
             &[&[Type::PathBufRef, Type::OsStr, Type::U8Slice]]
         }
@@ -478,7 +566,10 @@ fn direct_conversion(t1: Type, t2: Type) -> Conversion {
         // From String
         (Type::StringRef, Type::Str) => mkconv("{}.as_str()"),
         (Type::StringRef, Type::U8Slice) => mkconv("{}.as_bytes()"),
-        (Type::String, Type::U8Vec) => mkconv("{}.into_bytes()"),
+        (Type::String, Type::U8Vec) => mkconv("{}.into_bytes()").with_comment(
+            "This reuses the input's allocation: the result has the same
+pointer and capacity as the input, and no bytes are copied.",
+        ),
         (Type::StringRef, Type::Path) => mkconv("Path::new({})"),
         (Type::StringRef, Type::PathBuf) => mkconv("PathBuf::from({})"),
         (Type::StringRef, Type::OsStr) => mkconv("OsStr::new({})"),
@@ -511,7 +602,11 @@ fn direct_conversion(t1: Type, t2: Type) -> Conversion {
             mkconv("std::str::from_utf8({})")
         }
         (Type::U8Vec, Type::ResultStringOrFromUtf8Error) => {
-            mkconv("String::from_utf8({})")
+            mkconv("String::from_utf8({})").with_comment(
+                "On success this reuses the input's allocation: the result
+has the same pointer and capacity as the input, and no bytes are copied.
+On failure the input can be recovered with `FromUtf8Error::into_bytes`.",
+            )
         }
         (Type::U8VecRef, Type::U8Slice) => mkconv("{}.as_slice()"),
         (Type::U8VecRef, Type::OsStr) => {
@@ -544,6 +639,7 @@ fn direct_conversion(t1: Type, t2: Type) -> Conversion {
         (Type::OsString, Type::ResultStringOrOsString) => {
             mkconv("{}.into_string()")
         }
+        (Type::ResultStringOrOsString, Type::OptionString) => mkconv("{}.ok()"),
         (Type::OsStringRef, Type::U8Slice) => {
             mkconv("{}.as_bytes()").use_os_str_bytes()
         }
@@ -572,7 +668,12 @@ fn direct_conversion(t1: Type, t2: Type) -> Conversion {
         (Type::CStr, Type::ResultStrOrUtf8Error) => mkconv("{}.to_str()"),
         // TODO: add comment about the with nul option
         (Type::CStr, Type::U8Slice) => mkconv("{}.to_bytes()"),
-        (Type::CStr, Type::CString) => mkconv("CString::from({})"),
+        (Type::CStr, Type::CString) => mkconv("CString::from({})")
+            .with_comment(
+                "This always allocates and copies the bytes, since the
+input is borrowed. If you already have a CString, use it directly rather
+than converting through a &CStr.",
+            ),
 
         // From CString
         (Type::CStringRef, Type::CStr) => mkconv("{}.as_c_str()"),
@@ -580,7 +681,10 @@ fn direct_conversion(t1: Type, t2: Type) -> Conversion {
             mkconv("{}.into_string()")
         }
         (Type::CStringRef, Type::U8Slice) => mkconv("{}.as_bytes()"),
-        (Type::CString, Type::U8Vec) => mkconv("{}.into_bytes()"),
+        (Type::CString, Type::U8Vec) => mkconv("{}.into_bytes()").with_comment(
+            "This reuses the input's allocation. The nul terminator is
+dropped and no bytes are copied.",
+        ),
 
         (Type::ResultStrOrUtf8Error, Type::ResultStringOrUtf8Error) => {
             mkconv("{}.map(|s| s.to_string())")
@@ -622,13 +726,17 @@ impl Comment {
 
 #[derive(Default)]
 struct Code {
-    uses: BTreeSet<&'static str>,
+    uses: BTreeSet<String>,
+    /// Uses that are only needed by Unix-only code. These are gated on
+    /// `cfg(unix)` so that the crate builds without warnings on other
+    /// platforms.
+    unix_uses: BTreeSet<String>,
     functions: String,
 }
 
 impl Code {
     /// Combine some use lines together for brevity.
-    fn combine_uses(&self) -> BTreeSet<String> {
+    fn combine_uses(uses: &BTreeSet<String>) -> BTreeSet<String> {
         let combos = &[
             ("std::ffi", "CStr", "CString"),
             ("std::ffi", "OsStr", "OsString"),
@@ -636,12 +744,7 @@ impl Code {
             ("std::path", "Path", "PathBuf"),
         ];
 
-        // Make a copy of `uses` with `String` instead of `&str`
-        let mut uses = self
-            .uses
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<BTreeSet<_>>();
+        let mut uses = uses.clone();
 
         for (pre, a, b) in combos {
             let full_a = format!("{}::{}", pre, a);
@@ -656,17 +759,57 @@ impl Code {
         uses
     }
 
+    /// Append hand-written code. Each `use` line is merged into the
+    /// generated uses, so it must import a single path. Imports from
+    /// `std::os::unix`, and imports directly after a `#[cfg(unix)]`
+    /// line, are treated as Unix-only.
+    fn add_extra(&mut self, extra: &str) {
+        let mut body = String::new();
+        let mut unix_only = false;
+        for line in extra.lines() {
+            if let Some(path) =
+                line.strip_prefix("use ").and_then(|s| s.strip_suffix(';'))
+            {
+                if unix_only || path.starts_with("std::os::unix::") {
+                    self.unix_uses.insert(path.to_string());
+                } else {
+                    self.uses.insert(path.to_string());
+                }
+                unix_only = false;
+            } else if line == "#[cfg(unix)]" {
+                unix_only = true;
+            } else {
+                if unix_only {
+                    body.push_str("#[cfg(unix)]\n");
+                    unix_only = false;
+                }
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+
+        self.functions.push_str(body.trim());
+        self.functions.push('\n');
+    }
+
     fn gen(&self) -> String {
-        let uses = self.combine_uses();
+        let unix_uses = self
+            .unix_uses
+            .difference(&self.uses)
+            .cloned()
+            .collect::<BTreeSet<_>>();
 
-        format!(
-            "{}\n\n{}",
-            uses.iter()
-                .map(|s| format!("use {};", s))
-                .collect::<Vec<_>>()
-                .join("\n"),
-            self.functions
-        )
+        let uses = Code::combine_uses(&self.uses)
+            .iter()
+            .map(|s| format!("use {};", s))
+            .chain(
+                Code::combine_uses(&unix_uses)
+                    .iter()
+                    .map(|s| format!("#[cfg(unix)]\nuse {};", s)),
+            )
+            .collect::<Vec<_>>();
+
+        format!("{}\n\n{}", uses.join("\n"), self.functions)
     }
 }
 
@@ -675,22 +818,29 @@ fn gen_one_conversion(
     anchor2: Type,
     chain: &'static [Type],
     code: &mut Code,
+    traced: &mut Code,
 ) {
     let mut expr = "input".to_string();
 
     let input_type = chain.first().unwrap();
     let output_type = chain.last().unwrap();
     let mut unix_only = false;
+    let mut conv_comments = Vec::new();
+    let mut type_uses = BTreeSet::new();
+    let mut conv_uses = BTreeSet::new();
 
     for (t3, t4) in chain.iter().zip(chain.iter().skip(1)) {
         let conv = direct_conversion(*t3, *t4);
         expr = conv.format_expr(expr);
-        code.uses.extend(t3.uses());
-        code.uses.extend(t4.uses());
-        code.uses.extend(conv.uses());
+        type_uses.extend(t3.uses().iter().map(|s| s.to_string()));
+        type_uses.extend(t4.uses().iter().map(|s| s.to_string()));
+        conv_uses.extend(conv.uses().iter().map(|s| s.to_string()));
         if conv.unix_only() {
             unix_only = true;
         }
+        if let Some(comment) = conv.comment {
+            conv_comments.push(comment);
+        }
     }
 
     let mut suffix = String::new();
@@ -701,22 +851,67 @@ fn gen_one_conversion(
         suffix.push_str("_lossy");
     }
 
-    let func = format!(
-        "pub fn {}_to_{}{}(input: {}) -> {} {{\n    {}\n}}",
+    let name = format!(
+        "{}_to_{}{}",
         anchor1.short_name(),
         anchor2.short_name(),
-        suffix,
+        suffix
+    );
+
+    let cfg = if unix_only { "#[cfg(unix)]\n" } else { "" };
+
+    let func = format!(
+        "{}pub fn {}(input: {}) -> {} {{\n    {}\n}}",
+        cfg,
+        name,
         input_type.type_str(),
         output_type.type_str(),
         expr
     );
 
+    if let Some((ok_type, err_type)) = output_type.result_parts() {
+        if unix_only {
+            traced.unix_uses.extend(type_uses.iter().cloned());
+        } else {
+            traced.uses.extend(type_uses.iter().cloned());
+        }
+        traced.functions.push_str(&format!(
+            "// Like `from_{from}::{name}`, but the error names the
+// conversion.
+{cfg}pub fn {name}_traced(input: {input}) -> Result<{ok}, Traced<{err}>> {{
+    crate::from_{from}::{name}(input).map_err(|err| {{
+        Traced::new(\"{from}\", \"{to}\", \"{name}\", err)
+    }})
+}}
+
+",
+            cfg = cfg,
+            from = anchor1.short_name(),
+            to = anchor2.short_name(),
+            name = name,
+            input = input_type.type_str(),
+            ok = ok_type,
+            err = err_type,
+        ));
+    }
+    if unix_only {
+        code.unix_uses.extend(type_uses);
+        code.unix_uses.extend(conv_uses);
+    } else {
+        code.uses.extend(type_uses);
+        code.uses.extend(conv_uses);
+    }
+
     let mut comment = Comment::new();
 
     if unix_only {
         comment.add_paragraph("This conversion is only allowed on Unix.");
     }
 
+    for para in conv_comments {
+        comment.add_paragraph(para);
+    }
+
     if let Some(para) = output_type.return_comment() {
         comment.add_paragraph(para);
     }
@@ -726,7 +921,7 @@ fn gen_one_conversion(
     code.functions.push_str("\n\n");
 }
 
-fn gen_code(t1: Type) -> Code {
+fn gen_code(t1: Type, traced: &mut Code) -> Code {
     let mut code = Code::default();
     for t2 in Type::anchors() {
         if t1 == *t2 {
@@ -735,12 +930,60 @@ fn gen_code(t1: Type) -> Code {
 
         let chains = conversion_chains(t1, *t2);
         for chain in chains {
-            gen_one_conversion(t1, *t2, chain, &mut code);
+            gen_one_conversion(t1, *t2, chain, &mut code, traced);
         }
     }
     code
 }
 
+/// Directory of hand-written code for conversions that can't be
+/// expressed as a chain of direct conversions.
+///
+/// If `extra/from_<type>.rs` exists its contents are appended to the
+/// generated module for that type. Any other file is copied into
+/// `gen/src` as a module of its own.
+const EXTRA_DIR: &str = "extra";
+
+/// Module holding a `_traced` variant of each fallible generated
+/// conversion. The `Traced` error type it uses is defined in
+/// `extra/traced.rs`.
+const TRACED_MOD: &str = "traced";
+
+#[throws]
+fn read_extra(mod_name: &str) -> Option<String> {
+    let path = Path::new(EXTRA_DIR).join(format!("{}.rs", mod_name));
+    if path.exists() {
+        Some(fs::read_to_string(path)?)
+    } else {
+        None
+    }
+}
+
+/// Get the names of the extra modules that aren't appended to a
+/// generated module.
+#[throws]
+fn extra_mod_names() -> Vec<String> {
+    let mut generated = Type::anchors()
+        .iter()
+        .map(|t| format!("from_{}", t.short_name()))
+        .collect::<Vec<_>>();
+    generated.push(TRACED_MOD.to_string());
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(EXTRA_DIR)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("rs") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+        if !generated.contains(&name) {
+            names.push(name);
+        }
+    }
+    names.sort();
+    names
+}
+
 #[throws]
 fn run_cargo_cmd(cmd: &str) {
     Command::new("cargo").add_arg(cmd).set_dir("gen").run()?;
@@ -767,24 +1010,57 @@ fn gen_lib_code(mod_names: &[String]) -> String {
     )
 }
 
+/// Paths of the Rust files written to `gen/src`.
+struct GenOutput {
+    /// Module for each anchor type, keyed by the type converted from.
+    anchor_mods: Vec<(Type, PathBuf)>,
+    /// Modules that aren't tied to one anchor type, keyed by module
+    /// name.
+    other_mods: Vec<(String, PathBuf)>,
+}
+
 /// Generate the Rust files, format them, run clippy, and build.
-///
-/// Returns a vec mapping from the type being converted from to the
-/// path of the generated Rust file.
 #[throws]
-fn gen_and_build_sources() -> Vec<(Type, PathBuf)> {
+fn gen_and_build_sources() -> GenOutput {
     let gen_path = Path::new("gen/src");
     let mut mods = Vec::new();
-    let mut out = Vec::new();
+    let mut out = GenOutput {
+        anchor_mods: Vec::new(),
+        other_mods: Vec::new(),
+    };
+
+    let mut traced = Code::default();
+    if let Some(extra) = read_extra(TRACED_MOD)? {
+        traced.add_extra(&extra);
+        traced.functions.push('\n');
+    }
 
     for t1 in Type::anchors() {
         let mod_name = format!("from_{}", t1.short_name());
         mods.push(mod_name.clone());
 
+        let mut code = gen_code(*t1, &mut traced);
+        if let Some(extra) = read_extra(&mod_name)? {
+            code.add_extra(&extra);
+        }
+
+        let path = gen_path.join(format!("{}.rs", mod_name));
+        fs::write(&path, code.gen())?;
+        out.anchor_mods.push((*t1, path));
+    }
+
+    let path = gen_path.join(format!("{}.rs", TRACED_MOD));
+    fs::write(&path, traced.gen())?;
+    mods.push(TRACED_MOD.to_string());
+    out.other_mods.push((TRACED_MOD.to_string(), path));
+
+    for mod_name in extra_mod_names()? {
         let path = gen_path.join(format!("{}.rs", mod_name));
-        fs::write(&path, gen_code(*t1).gen())?;
-        out.push((*t1, path));
+        fs::copy(Path::new(EXTRA_DIR).join(format!("{}.rs", mod_name)), &path)?;
+        mods.push(mod_name.clone());
+        out.other_mods.push((mod_name, path));
     }
+    out.other_mods.sort();
 
     fs::write(gen_path.join("lib.rs"), gen_lib_code(&mods))?;
 
@@ -877,20 +1153,69 @@ impl DocLink {
     }
 }
 
+/// Anchor name and heading of the docs section listing the modules
+/// that aren't tied to one anchor type.
+const OTHER_MODS_ANCHOR: &str = "modules";
+const OTHER_MODS_NAME: &str = "Other modules";
+
+/// Read a generated file for display, leaving out its unit tests.
+///
+/// Tests are always the last item in a file, so everything from the
+/// first test attribute on is dropped.
 #[throws]
-fn gen_html_content(gen: &[(Type, PathBuf)]) -> String {
+fn read_without_tests(path: &Path) -> String {
+    let code = fs::read_to_string(path)?;
+    let end = code
+        .find("#[cfg(test)]")
+        .into_iter()
+        .chain(code.find("#[cfg(all(test"))
+        .min()
+        .unwrap_or(code.len());
+    format!("{}\n", code[..end].trim_end())
+}
+
+#[throws]
+fn gen_html_content(gen: &GenOutput) -> String {
     let mut out = String::new();
     let highlighter = Highlighter::new();
 
-    for (t1, path) in gen {
-        let code = fs::read_to_string(path)?;
+    for category in Category::all() {
+        out = format!(
+            "{}<a name={}><h2>{}</h2></a>",
+            out,
+            category.short_name(),
+            category.name(),
+        );
+
+        for (t1, path) in gen
+            .anchor_mods
+            .iter()
+            .filter(|(t, _)| t.category() == *category)
+        {
+            let code = read_without_tests(path)?;
+            let highlighted = highlighter.highlight(&code);
+
+            out = format!(
+                "{}<a name={}><h3>From <code>{}</code></h3></a>",
+                out,
+                t1.short_name(),
+                t1.html_type_str(),
+            );
+            out.push_str(&highlighted);
+        }
+    }
+
+    out = format!(
+        "{}<a name={}><h2>{}</h2></a>",
+        out, OTHER_MODS_ANCHOR, OTHER_MODS_NAME,
+    );
+    for (mod_name, path) in &gen.other_mods {
+        let code = read_without_tests(path)?;
         let highlighted = highlighter.highlight(&code);
 
         out = format!(
-            "{}<a name={}><h2>From <code>{}</code></h2></a>",
-            out,
-            t1.short_name(),
-            t1.html_type_str(),
+            "{}<a name=mod_{}><h3>Module <code>{}</code></h3></a>",
+            out, mod_name, mod_name,
         );
         out.push_str(&highlighted);
     }
@@ -952,17 +1277,36 @@ fn gen_html_content(gen: &[(Type, PathBuf)]) -> String {
     out
 }
 
-fn gen_html_nav() -> String {
-    let mut nav = "<ul>".to_string();
-    for a in Type::anchors() {
+fn gen_html_nav(gen: &GenOutput) -> String {
+    let mut nav = String::new();
+    for category in Category::all() {
         nav = format!(
-            "{}<li><a href=\"#{}\">From <code>{}</code></a></li>",
+            "{}<details open><summary>{}</summary><ul>",
             nav,
-            a.short_name(),
-            a.html_type_str()
+            category.name()
+        );
+        for a in category.anchors() {
+            nav = format!(
+                "{}<li><a href=\"#{}\">From <code>{}</code></a></li>",
+                nav,
+                a.short_name(),
+                a.html_type_str()
+            );
+        }
+        nav += "</ul></details>";
+    }
+
+    nav = format!(
+        "{}<details open><summary>{}</summary><ul>",
+        nav, OTHER_MODS_NAME
+    );
+    for (mod_name, _) in &gen.other_mods {
+        nav = format!(
+            "{}<li><a href=\"#mod_{}\"><code>{}</code></a></li>",
+            nav, mod_name, mod_name
         );
     }
-    nav += "</ul>";
+    nav += "</ul></details>";
     nav
 }
 
@@ -971,7 +1315,7 @@ fn main() {
     let gen = gen_and_build_sources()?;
 
     IndexTemplate {
-        nav: gen_html_nav(),
+        nav: gen_html_nav(&gen),
         rust_version: get_rustc_version()?,
         content: gen_html_content(&gen)?,
     }